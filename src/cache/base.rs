@@ -4,7 +4,6 @@ Cacher interface
 
 */
 
-use crate::Args;
 use serde::{de::DeserializeOwned, Serialize};
 
 #[cfg(feature = "sled")]
@@ -12,6 +11,7 @@ use super::sled::Db;
 
 #[cfg(not(feature = "sled"))]
 use super::file::Db;
+use std::path::Path;
 
 /// Cache store
 pub struct Cache {
@@ -19,14 +19,11 @@ pub struct Cache {
 }
 
 impl Cache {
-    pub fn open(args: &Args, group: impl AsRef<str>) -> Self {
-        let group = group.as_ref();
-        let db = if args.no_cache {
-            None
-        } else {
-            let path = args.cache_dir.as_ref().unwrap().join(group);
-            Self::_open(&path)
-        };
+    /// Open cache group in directory
+    ///
+    /// Caching is disabled when directory isn't given.
+    pub fn open(dir: Option<&Path>, group: impl AsRef<str>) -> Self {
+        let db = dir.and_then(|dir| Self::_open(&dir.join(group.as_ref())));
         Self { db }
     }
 
@@ -1,10 +1,11 @@
-use crate::Args;
+use std::path::Path;
 
 /// Cache store
 pub struct Cache;
 
 impl Cache {
-    pub fn open(_args: &Args, _group: impl AsRef<str>) -> Self {
+    /// Open cache group in directory
+    pub fn open(_dir: Option<&Path>, _group: impl AsRef<str>) -> Self {
         Self
     }
 
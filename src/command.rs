@@ -7,14 +7,16 @@ Command-line arguments and command processing
 pub(self) use std::path::{Path, PathBuf};
 pub(self) use structopt::StructOpt;
 
-pub(self) use crate::{utils, Error, Result, State};
+pub(self) use crate::{utils, Error, Result, State, Status};
 
 #[cfg(any(
     feature = "jsonschema",
     feature = "jsonschema-valid",
     feature = "valico",
 ))]
-pub(self) use crate::{CompiledSchema, Standard, Validator};
+pub(self) use crate::{
+    Branches, Comparison, CompiledSchema, FormatMode, OutputFormat, Reporter, Standard, Validator,
+};
 
 #[cfg(any(
     feature = "jsonschema",
@@ -24,14 +26,28 @@ pub(self) use crate::{CompiledSchema, Standard, Validator};
 ))]
 pub(self) use crate::Format;
 
+#[cfg(any(
+    feature = "jsonschema",
+    feature = "jsonschema-valid",
+    feature = "valico",
+))]
+mod input;
+
+#[cfg(any(
+    feature = "jsonschema",
+    feature = "jsonschema-valid",
+    feature = "valico",
+))]
+use input::{Inputs, Report, Totals};
+
 /// Command result
-pub type CmdResult = Result<u32>;
+pub type CmdResult = Result<Status>;
 
 #[cfg(feature = "http_req")]
 pub(self) use crate::Uri;
 
 /// Logging levels list
-const LOG_LEVELS: &[&str] = &["error", "warn", "info", "debug", "trace"];
+pub const LOG_LEVELS: &[&str] = &["error", "warn", "info", "debug", "trace"];
 
 macro_rules! decl_commands {
     ($(
@@ -88,6 +104,30 @@ decl_commands! {
         feature = "valico"
     ))]
     Validate validate;
+
+    /// Compare validation results of all validators
+    #[cfg(any(
+        feature = "jsonschema",
+        feature = "jsonschema-valid",
+        feature = "valico"
+    ))]
+    Compare compare;
+
+    /// Check schema against meta-schema
+    #[cfg(any(
+        feature = "jsonschema",
+        feature = "jsonschema-valid",
+        feature = "valico"
+    ))]
+    CheckSchema check_schema;
+
+    /// Fill missing values with schema defaults
+    #[cfg(any(
+        feature = "jsonschema",
+        feature = "jsonschema-valid",
+        feature = "valico"
+    ))]
+    FillDefaults fill_defaults;
 }
 
 /// Command-line arguments
@@ -129,6 +169,33 @@ pub struct Args {
     )]
     pub catalog_url: Uri,
 
+    #[cfg(any(
+        feature = "jsonschema",
+        feature = "jsonschema-valid",
+        feature = "valico"
+    ))]
+    /// Directories with local schemas which is referenced by its ids
+    #[structopt(short = "S", long, env, number_of_values = 1)]
+    pub schema_dir: Vec<PathBuf>,
+
+    #[cfg(any(
+        feature = "jsonschema",
+        feature = "jsonschema-valid",
+        feature = "valico"
+    ))]
+    /// Files with custom formats (object of format names and regular expressions)
+    #[structopt(long, env, number_of_values = 1)]
+    pub formats: Vec<PathBuf>,
+
+    #[cfg(any(
+        feature = "jsonschema",
+        feature = "jsonschema-valid",
+        feature = "valico"
+    ))]
+    /// Treat format keyword as assertion or annotation
+    #[structopt(long, env, default_value = "assertion", possible_values = FormatMode::LIST)]
+    pub format_mode: FormatMode,
+
     /// Command to execute
     #[structopt(subcommand)]
     pub command: Command,
@@ -140,21 +207,50 @@ impl Args {
         self.command.run(self, state)
     }
 
+    /// Fill default cache directory unless caching is disabled
+    #[cfg(feature = "cache")]
+    pub fn fix_cache(&mut self) {
+        if self.no_cache {
+            self.cache_dir = None;
+        } else if self.cache_dir.is_none() {
+            self.cache_dir = dirs::cache_dir().map(|path| path.join(env!("CARGO_PKG_NAME")));
+            if self.cache_dir.is_none() {
+                self.no_cache = true;
+                log::warn!("Unable to guess cache directory");
+            }
+        }
+    }
+
+    #[cfg(not(feature = "cache"))]
+    pub fn fix_cache(&mut self) {}
+
+    /// Cache directory or nothing when caching is disabled
+    pub fn cache_dir(&self) -> Option<&Path> {
+        #[cfg(feature = "cache")]
+        {
+            self.cache_dir.as_deref()
+        }
+
+        #[cfg(not(feature = "cache"))]
+        {
+            None
+        }
+    }
+
     /// Check output filepath to prevent unwanted overwriting
     pub fn check_output_file(&self, path: impl AsRef<Path>) -> Result<()> {
         let path = path.as_ref();
         if path.is_dir() {
-            log::error!(
-                "Output path '{}' is directory and cannot be overwitten in any case",
-                path.display()
-            );
-            return Err(Error::Conflict);
+            return Err(Error::Conflict {
+                path: path.into(),
+                reason: "is directory and cannot be overwitten in any case",
+            });
         } else if path.is_file() && !self.force {
-            log::error!(
-                "Output file '{}' already exists and wont be overwritten. Use -f option to force overwriting",
-                path.display()
-            );
-            return Err(Error::Conflict);
+            return Err(Error::Conflict {
+                path: path.into(),
+                reason:
+                    "already exists and wont be overwritten. Use -F option to force overwriting",
+            });
         }
         Ok(())
     }
@@ -0,0 +1,74 @@
+use super::{
+    validate::read_parse, Args, CmdResult, Error, Format, Inputs, OutputFormat, Path, Report,
+    Reporter, Result, Standard, State, Status, StructOpt, Validator,
+};
+
+#[derive(StructOpt, Debug)]
+pub struct Command {
+    /// Schema format
+    #[structopt(short, long, possible_values = Format::LIST)]
+    pub format: Option<Format>,
+
+    /// Using standard: draft4, draft6, draft7, draft2019-09 or draft2020-12 (detected from $schema by default)
+    #[structopt(short, long, possible_values = Standard::NAMES, hide_possible_values = true)]
+    pub standard: Option<Standard>,
+
+    /// Using validator (another one will be used when it doesn't support standard)
+    #[structopt(short, long, default_value = Validator::LIST[0], possible_values = Validator::LIST)]
+    pub validator: Validator,
+
+    /// Output format (structured formats prints one JSON document per input)
+    #[structopt(short, long, default_value = "text", possible_values = OutputFormat::LIST)]
+    pub output_format: OutputFormat,
+
+    #[structopt(flatten)]
+    pub inputs: Inputs,
+}
+
+impl Command {
+    pub fn run(&self, args: &Args, _state: &State) -> CmdResult {
+        let totals = self
+            .inputs
+            .for_each("JSON Schema", 1, |topic, path, input| {
+                self.read_parse_check(args, topic, path, input)
+            })?;
+
+        if !args.quiet && self.output_format == OutputFormat::Text && totals.inputs > 1 {
+            println!(
+                "Checked {} schemas: {} valid, {} not valid, {} errors total",
+                totals.inputs,
+                totals.inputs - totals.failed,
+                totals.failed,
+                totals.errors
+            );
+        }
+
+        Ok(Status::from_failures(totals.failed))
+    }
+
+    fn read_parse_check(
+        &self,
+        args: &Args,
+        topic: &str,
+        path: &Path,
+        input: &mut dyn std::io::Read,
+    ) -> Result<Report> {
+        let (data, format, schema) =
+            read_parse(self.format, topic, path, input).map_err(Error::into_schema)?;
+        let errors = self.validator.check_schema(&schema, self.standard)?;
+
+        let report = if args.quiet {
+            String::new()
+        } else {
+            let source_map = if !errors.is_empty() {
+                format.source_map(&data)
+            } else {
+                None
+            };
+            let reporter = Reporter::new(self.output_format, args.verbose).with_subject("Schema");
+            reporter.report(path, source_map.as_ref(), &schema, &errors)
+        };
+
+        Ok((report, errors.len() as u32))
+    }
+}
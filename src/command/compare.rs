@@ -0,0 +1,96 @@
+use super::{
+    validate::{load_schema, read_parse},
+    Args, CmdResult, Comparison, CompiledSchema, Error, Format, Inputs, Path, PathBuf, Report,
+    Result, Standard, State, Status, StructOpt, Validator,
+};
+
+#[derive(StructOpt, Debug)]
+pub struct Command {
+    /// Input data format
+    #[structopt(short, long, possible_values = Format::LIST)]
+    pub format: Option<Format>,
+
+    /// Using standard: draft4, draft6, draft7, draft2019-09 or draft2020-12 (detected from $schema by default)
+    #[structopt(short, long, possible_values = Standard::NAMES, hide_possible_values = true)]
+    pub standard: Option<Standard>,
+
+    #[cfg(feature = "schemastore")]
+    /// Schema file or name
+    #[structopt()]
+    pub schema: PathBuf,
+
+    #[cfg(not(feature = "schemastore"))]
+    /// Schema file
+    #[structopt()]
+    pub schema: PathBuf,
+
+    #[structopt(flatten)]
+    pub inputs: Inputs,
+}
+
+impl Command {
+    /// Fails when validators disagree on any input
+    pub fn run(&self, args: &Args, state: &State) -> CmdResult {
+        let (schema, _) = load_schema(&self.schema, state)?;
+        let schema = state.formats.apply(&schema);
+
+        let schemas = Validator::ALL
+            .iter()
+            .filter_map(
+                |validator| match validator.compile_schema(&schema, self.standard) {
+                    Ok(schema) => Some((*validator, schema)),
+                    Err(_) => {
+                        log::warn!("Validator {} is excluded from comparison", validator);
+                        None
+                    }
+                },
+            )
+            .collect::<Vec<_>>();
+
+        if schemas.is_empty() {
+            return Err(Error::Compile(
+                "JSON Schema cannot be compiled by any validator".into(),
+            ));
+        }
+        if schemas.len() < 2 {
+            log::warn!("Only one validator is available so nothing to compare");
+        }
+
+        let totals = self.inputs.for_each("data", 1, |topic, path, input| {
+            self.read_parse_compare(args, topic, &schemas, path, input)
+        })?;
+
+        if !args.quiet && totals.inputs > 1 {
+            println!(
+                "Compared {} inputs: validators disagree on {}",
+                totals.inputs, totals.failed
+            );
+        }
+
+        Ok(Status::from_failures(totals.failed))
+    }
+
+    fn read_parse_compare(
+        &self,
+        args: &Args,
+        topic: &str,
+        schemas: &[(Validator, CompiledSchema)],
+        path: &Path,
+        input: &mut dyn std::io::Read,
+    ) -> Result<Report> {
+        let (_, _, value) = read_parse(self.format, topic, path, input)?;
+
+        let mut comparison = Comparison::default();
+        for (validator, schema) in schemas {
+            comparison.push(*validator, schema.validate_data(&value)?);
+        }
+
+        let report = if args.quiet {
+            String::new()
+        } else {
+            comparison.report(path, args.verbose)
+        };
+
+        Ok((report, if comparison.agree() { 0 } else { 1 }))
+    }
+}
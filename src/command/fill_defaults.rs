@@ -0,0 +1,140 @@
+use super::{
+    utils, Args, Branches, CmdResult, Error, Format, OutputFormat, Path, PathBuf, Reporter,
+    Standard, State, Status, StructOpt, Validator,
+};
+use crate::fill_defaults;
+
+#[derive(StructOpt, Debug)]
+pub struct Command {
+    /// Input data format
+    #[structopt(short, long, possible_values = Format::LIST)]
+    pub format: Option<Format>,
+
+    /// Output data format (detected from output file name or same as input by default)
+    #[structopt(short, long, possible_values = Format::LIST)]
+    pub to: Option<Format>,
+
+    /// Using standard: draft4, draft6, draft7, draft2019-09 or draft2020-12 (detected from $schema by default)
+    #[structopt(short, long, possible_values = Standard::NAMES, hide_possible_values = true)]
+    pub standard: Option<Standard>,
+
+    /// Using validator
+    #[structopt(short, long, default_value = Validator::LIST[0], possible_values = Validator::LIST)]
+    pub validator: Validator,
+
+    /// Pretty formatted output
+    #[structopt(short, long)]
+    pub pretty: bool,
+
+    /// Schema file, URL or name
+    #[structopt()]
+    pub schema: PathBuf,
+
+    /// Input data file name
+    #[structopt()]
+    pub input: Option<PathBuf>,
+
+    /// Output data file name
+    #[structopt(short, long)]
+    pub output: Option<PathBuf>,
+}
+
+impl Command {
+    pub fn run(&self, args: &Args, state: &State) -> CmdResult {
+        let topic = "data";
+        if let Some(path) = &self.input {
+            let mut file = utils::open_file(topic, path)?;
+            self.read_and_fill(args, state, topic, path, &mut file)
+        } else {
+            let path = Path::new("stdin");
+            let mut file = std::io::stdin();
+            self.read_and_fill(args, state, topic, path, &mut file)
+        }
+    }
+
+    /// Fill defaults of each document and write it when all is valid
+    fn read_and_fill(
+        &self,
+        args: &Args,
+        state: &State,
+        topic: &str,
+        path: &Path,
+        input: &mut dyn std::io::Read,
+    ) -> CmdResult {
+        let (schema, _) = super::validate::load_schema(&self.schema, state)?;
+        let formatted = state.formats.apply(&schema);
+        let compiled = self.validator.compile_schema(&formatted, self.standard)?;
+
+        let data = utils::read_input(topic, path, input)?;
+        let format = self
+            .format
+            .or_else(|| Format::from_path(path))
+            .ok_or_else(|| Error::UnknownFormat {
+                topic: topic.into(),
+                path: path.into(),
+            })?;
+        // all records is required to write the document back
+        let mut documents = format
+            .parse_documents(topic, path, &data)?
+            .into_iter()
+            .map(|(index, document)| document.map(|document| (index, document)))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        // records of streams is reported as `path#index`
+        let by_records = format.is_stream() || documents.len() > 1;
+        let branches = Branches::new(&compiled, self.standard);
+        let reporter = Reporter::new(OutputFormat::Text, args.verbose).with_best_matches(&branches);
+        let mut source_maps = None;
+        let mut failed = 0;
+
+        for (index, document) in &mut documents {
+            fill_defaults(&schema, document);
+            let errors = compiled.validate_data(document)?;
+            if errors.is_empty() {
+                continue;
+            }
+            failed += 1;
+
+            if !args.quiet {
+                let source_map = source_maps
+                    .get_or_insert_with(|| format.source_maps(&data))
+                    .get(*index);
+                let name = if by_records {
+                    PathBuf::from(format!("{}#{}", path.display(), index))
+                } else {
+                    path.into()
+                };
+                eprint!("{}", reporter.report(&name, source_map, document, &errors));
+            }
+        }
+
+        if failed > 0 {
+            log::error!("Data with defaults is not valid so it isn't written");
+            return Ok(Status::Failure);
+        }
+
+        let output_format = self
+            .to
+            .or_else(|| Format::from_path(self.output.as_deref()?))
+            .unwrap_or(format);
+        let documents = documents
+            .into_iter()
+            .map(|(_, document)| document)
+            .collect::<Vec<_>>();
+        let contents = output_format.format_documents(topic, &documents, self.pretty)?;
+
+        if let Some(path) = &self.output {
+            log::info!("Saving {} to file '{}'...", topic, path.display());
+
+            args.check_output_file(path)?;
+            let mut file = utils::create_file(topic, path)?;
+            utils::write_output(topic, path, &mut file, &contents)?;
+        } else {
+            let path = Path::new("stdout");
+            let mut file = std::io::stdout();
+            utils::write_output(topic, path, &mut file, &contents)?;
+        }
+
+        Ok(Status::Success)
+    }
+}
@@ -1,4 +1,4 @@
-use super::{utils, Args, CmdResult, Error, Format, Path, PathBuf, State, StructOpt};
+use super::{utils, Args, CmdResult, Error, Format, Path, PathBuf, State, Status, StructOpt};
 
 #[derive(StructOpt, Debug)]
 pub struct Command {
@@ -43,16 +43,12 @@ impl Command {
         let format = self
             .format
             .or_else(|| Format::from_path(path))
-            .ok_or_else(|| {
-                log::error!(
-                    "Format of {} from '{}' is not given and cannot to be inferred from filename. Try use -f option to set it.",
-                    topic,
-                    path.display()
-                );
-                Error::Parse
+            .ok_or_else(|| Error::UnknownFormat {
+                topic: topic.into(),
+                path: path.into(),
             })?;
 
-        let data = format.parse_data(topic, path, &data).ok_or(Error::Parse)?;
+        let data = format.parse_data(topic, path, &data)?;
 
         let schema = infers::JSONSchema::new(&data);
         //let schema = schema.detect_format(true);
@@ -73,6 +69,6 @@ impl Command {
             utils::write_output(topic, path, &mut file, &contents)?;
         }
 
-        Ok(0)
+        Ok(Status::Success)
     }
 }
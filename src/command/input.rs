@@ -0,0 +1,413 @@
+/*!
+
+Selecting and processing input files
+
+*/
+
+use super::{utils, Error, Format, Path, PathBuf, Result, StructOpt};
+use globset::{GlobBuilder, GlobMatcher};
+use ignore::{overrides::OverrideBuilder, WalkBuilder};
+use std::{
+    collections::BTreeMap,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        mpsc,
+    },
+};
+
+/// Input files selection
+#[derive(StructOpt, Clone, Debug)]
+pub struct Inputs {
+    /// Include only files which matches glob when walking directories
+    #[structopt(short = "I", long, number_of_values = 1)]
+    pub include: Vec<String>,
+
+    /// Exclude files which matches glob when walking directories
+    #[structopt(short = "E", long, number_of_values = 1)]
+    pub exclude: Vec<String>,
+
+    /// Don't respect ignore files like .gitignore when walking directories
+    #[structopt(long)]
+    pub no_ignore: bool,
+
+    /// Input files, directories or glob patterns (otherwise input will be read from stdin)
+    #[structopt()]
+    pub input: Vec<PathBuf>,
+}
+
+impl Inputs {
+    /// Expand directories and glob patterns to list of files
+    ///
+    /// Directories are walked recursively and only files of known formats is taken
+    /// unless include patterns is given.
+    pub fn expand(&self, topic: &str) -> Result<Vec<PathBuf>> {
+        let mut files = Vec::new();
+        for path in &self.input {
+            if path.is_file() {
+                files.push(path.clone());
+            } else if path.is_dir() {
+                self.walk(topic, path, None, &mut files)?;
+            } else if let Some((base, glob)) = split_glob(path) {
+                let glob = GlobBuilder::new(&glob)
+                    .literal_separator(true)
+                    .build()
+                    .map_err(|error| Error::Pattern {
+                        pattern: glob.clone(),
+                        source: error.into(),
+                    })?
+                    .compile_matcher();
+                // pattern matches nothing when base directory doesn't exist
+                if base.is_dir() {
+                    self.walk(topic, &base, Some(&glob), &mut files)?;
+                }
+            } else {
+                return Err(Error::Open {
+                    topic: topic.into(),
+                    path: path.clone(),
+                    source: std::io::ErrorKind::NotFound.into(),
+                });
+            }
+        }
+        Ok(files)
+    }
+
+    fn walk(
+        &self,
+        topic: &str,
+        root: &Path,
+        glob: Option<&GlobMatcher>,
+        files: &mut Vec<PathBuf>,
+    ) -> Result<()> {
+        let mut overrides = OverrideBuilder::new(root);
+        let patterns = self
+            .include
+            .iter()
+            .cloned()
+            .chain(self.exclude.iter().map(|pattern| format!("!{}", pattern)));
+        for pattern in patterns {
+            overrides.add(&pattern).map_err(|error| Error::Pattern {
+                pattern: pattern.clone(),
+                source: error.into(),
+            })?;
+        }
+        let overrides = overrides.build().map_err(|error| Error::Pattern {
+            pattern: self.include.join(" "),
+            source: error.into(),
+        })?;
+
+        // hidden files like .gitlab-ci.yml are usual inputs unlike git internals
+        let walker = WalkBuilder::new(root)
+            .standard_filters(!self.no_ignore)
+            .hidden(false)
+            .filter_entry(|entry| entry.file_name() != ".git")
+            .require_git(false)
+            .overrides(overrides)
+            .sort_by_file_name(|a, b| a.cmp(b))
+            .build();
+
+        for entry in walker {
+            let entry = entry.map_err(|error| Error::Read {
+                topic: format!("{} directory", topic),
+                path: root.into(),
+                source: std::io::Error::other(error),
+            })?;
+            if !entry
+                .file_type()
+                .map(|kind| kind.is_file())
+                .unwrap_or(false)
+            {
+                continue;
+            }
+            let path = entry.path();
+            let selected = if let Some(glob) = glob {
+                glob.is_match(path.strip_prefix(".").unwrap_or(path))
+            } else {
+                // explicitly included files may have any extension
+                !self.include.is_empty() || Format::from_path(path).is_some()
+            };
+            if selected {
+                files.push(path.into());
+            }
+        }
+        Ok(())
+    }
+
+    /// Process each input file or stdin when no inputs given
+    ///
+    /// Files are processed by given number of parallel jobs (0 means number of CPUs).
+    /// Reports are printed in order of inputs as soon as it is ready.
+    pub fn for_each(
+        &self,
+        topic: &str,
+        jobs: usize,
+        process: impl Fn(&str, &Path, &mut dyn std::io::Read) -> Result<Report> + Sync,
+    ) -> Result<Totals> {
+        let mut totals = Totals::default();
+
+        if self.input.is_empty() {
+            let path = Path::new("stdin");
+            let mut file = std::io::stdin();
+            let (report, errors) = process(topic, path, &mut file)?;
+            print!("{}", report);
+            totals.add(errors);
+            return Ok(totals);
+        }
+
+        let inputs = self.expand(topic)?;
+        if inputs.is_empty() {
+            log::warn!("No input {} files found", topic);
+            return Ok(totals);
+        }
+
+        process_files(topic, jobs, &inputs, process)
+    }
+}
+
+/// Process given input files
+///
+/// Files are processed by given number of parallel jobs (0 means number of CPUs).
+/// Reports are printed in order of inputs as soon as it is ready.
+pub fn process_files(
+    topic: &str,
+    jobs: usize,
+    inputs: &[PathBuf],
+    process: impl Fn(&str, &Path, &mut dyn std::io::Read) -> Result<Report> + Sync,
+) -> Result<Totals> {
+    let mut totals = Totals::default();
+
+    let jobs = jobs_count(jobs, inputs.len());
+    if jobs < 2 {
+        for path in inputs {
+            let (report, errors) = process_file(topic, path, &process)?;
+            print!("{}", report);
+            totals.add(errors);
+        }
+        return Ok(totals);
+    }
+
+    log::debug!("Processing {} inputs using {} jobs", inputs.len(), jobs);
+
+    let next = AtomicUsize::new(0);
+    let stop = AtomicBool::new(false);
+
+    std::thread::scope(|scope| {
+        let (sender, receiver) = mpsc::channel();
+
+        for _ in 0..jobs {
+            let sender = sender.clone();
+            let (next, stop, process) = (&next, &stop, &process);
+            scope.spawn(move || {
+                while !stop.load(Ordering::Relaxed) {
+                    let index = next.fetch_add(1, Ordering::Relaxed);
+                    if index >= inputs.len() {
+                        break;
+                    }
+                    let result = process_file(topic, &inputs[index], process);
+                    if sender.send((index, result)).is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+        drop(sender);
+
+        // results which is ready before preceding ones
+        let mut pending = BTreeMap::new();
+        for (index, result) in receiver {
+            pending.insert(index, result);
+            while let Some(result) = pending.remove(&(totals.inputs as usize)) {
+                let (report, errors) = result.inspect_err(|_| {
+                    // no need to process remaining inputs
+                    stop.store(true, Ordering::Relaxed);
+                })?;
+                print!("{}", report);
+                totals.add(errors);
+            }
+        }
+        Ok(totals)
+    })
+}
+
+/// Map given input files by parallel jobs keeping its order
+pub fn map_files<T: Send>(
+    jobs: usize,
+    inputs: &[PathBuf],
+    map: impl Fn(&Path) -> T + Sync,
+) -> Vec<T> {
+    let jobs = jobs_count(jobs, inputs.len());
+    if jobs < 2 {
+        return inputs.iter().map(|path| map(path)).collect();
+    }
+
+    let next = AtomicUsize::new(0);
+    let mut results = std::thread::scope(|scope| {
+        let threads = (0..jobs)
+            .map(|_| {
+                let (next, map) = (&next, &map);
+                scope.spawn(move || {
+                    let mut results = Vec::new();
+                    loop {
+                        let index = next.fetch_add(1, Ordering::Relaxed);
+                        match inputs.get(index) {
+                            Some(path) => results.push((index, map(path))),
+                            None => break results,
+                        }
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+        threads
+            .into_iter()
+            .flat_map(|thread| thread.join().expect("Mapping job panicked"))
+            .collect::<Vec<_>>()
+    });
+    results.sort_by_key(|(index, _)| *index);
+    results.into_iter().map(|(_, result)| result).collect()
+}
+
+/// Number of parallel jobs for inputs (0 means number of CPUs)
+fn jobs_count(jobs: usize, inputs: usize) -> usize {
+    if jobs == 0 {
+        std::thread::available_parallelism().map_or(1, |jobs| jobs.get())
+    } else {
+        jobs
+    }
+    .min(inputs)
+}
+
+/// Split glob pattern to base directory and pattern itself
+///
+/// Returns None when path isn't a glob pattern.
+fn split_glob(path: &Path) -> Option<(PathBuf, String)> {
+    let pattern = path.to_str()?;
+    if !pattern.contains(['*', '?', '[', '{']) {
+        return None;
+    }
+    // walked paths is matched without leading `./`
+    let pattern = pattern.strip_prefix("./").unwrap_or(pattern);
+    let base = path
+        .components()
+        .take_while(|component| {
+            !component
+                .as_os_str()
+                .to_string_lossy()
+                .contains(['*', '?', '[', '{'])
+        })
+        .collect::<PathBuf>();
+    let base = if base.as_os_str().is_empty() {
+        PathBuf::from(".")
+    } else {
+        base
+    };
+    Some((base, pattern.into()))
+}
+
+/// Totals of processed inputs
+#[derive(Default)]
+pub struct Totals {
+    /// Number of processed inputs
+    pub inputs: u32,
+    /// Number of inputs with errors
+    pub failed: u32,
+    /// Number of errors in all inputs
+    pub errors: u32,
+}
+
+impl Totals {
+    /// Add totals of other inputs
+    pub fn merge(&mut self, other: Totals) {
+        self.inputs += other.inputs;
+        self.failed += other.failed;
+        self.errors += other.errors;
+    }
+
+    /// Count inputs which cannot be processed as failed
+    pub fn add_unprocessed(&mut self, inputs: u32) {
+        self.inputs += inputs;
+        self.failed += inputs;
+    }
+
+    fn add(&mut self, errors: u32) {
+        self.inputs += 1;
+        if errors > 0 {
+            self.failed += 1;
+        }
+        self.errors += errors;
+    }
+}
+
+/// Report of processed input with number of errors
+pub type Report = (String, u32);
+
+fn process_file(
+    topic: &str,
+    path: &Path,
+    process: impl Fn(&str, &Path, &mut dyn std::io::Read) -> Result<Report>,
+) -> Result<Report> {
+    let mut file = utils::open_file(topic, path)?;
+    process(topic, path, &mut file)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn map_files_in_order() {
+        let inputs = (0..50)
+            .map(|index| PathBuf::from(index.to_string()))
+            .collect::<Vec<_>>();
+        let names = map_files(4, &inputs, |path| path.display().to_string());
+        assert_eq!(
+            names,
+            (0..50).map(|index| index.to_string()).collect::<Vec<_>>()
+        );
+    }
+
+    #[cfg(feature = "yaml")]
+    #[test]
+    fn walk_hidden_files() {
+        let dir = std::env::temp_dir().join(format!("jsonst-walk-{}", std::process::id()));
+        let workflows = dir.join(".github/workflows");
+        std::fs::create_dir_all(&workflows).unwrap();
+        std::fs::create_dir_all(dir.join(".git")).unwrap();
+        for path in [
+            workflows.join("ci.yml"),
+            dir.join(".gitlab-ci.yml"),
+            dir.join(".git/config.json"),
+        ] {
+            std::fs::write(path, "{}").unwrap();
+        }
+
+        let inputs = Inputs {
+            include: Vec::new(),
+            exclude: Vec::new(),
+            no_ignore: false,
+            input: vec![dir.clone()],
+        };
+        let files = inputs.expand("data").unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(
+            files,
+            [workflows.join("ci.yml"), dir.join(".gitlab-ci.yml")]
+        );
+    }
+
+    #[test]
+    fn split_globs() {
+        assert_eq!(split_glob(Path::new("deploy/app.yaml")), None);
+        assert_eq!(
+            split_glob(Path::new("deploy/**/*.yaml")),
+            Some(("deploy".into(), "deploy/**/*.yaml".into()))
+        );
+        assert_eq!(
+            split_glob(Path::new("*.{yml,yaml}")),
+            Some((".".into(), "*.{yml,yaml}".into()))
+        );
+        assert_eq!(
+            split_glob(Path::new("./deploy/*.json")),
+            Some(("./deploy".into(), "deploy/*.json".into()))
+        );
+    }
+}
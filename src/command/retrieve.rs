@@ -1,4 +1,4 @@
-use super::{utils, Args, CmdResult, Error, Path, PathBuf, State, StructOpt};
+use super::{utils, Args, CmdResult, Path, PathBuf, State, Status, StructOpt};
 
 #[derive(StructOpt, Debug)]
 pub struct Command {
@@ -17,11 +17,7 @@ pub struct Command {
 
 impl Command {
     pub fn run(&self, args: &Args, state: &State) -> CmdResult {
-        let contents = state
-            .schema_store
-            .get_one([&self.pattern], true, false)
-            .ok_or(Error::Query)?
-            .1;
+        let contents = state.schema_store.get_one([&self.pattern], true, false)?.1;
 
         let topic = "JSON Schema";
         let contents = utils::format_json(topic, &contents, self.pretty)?;
@@ -38,6 +34,6 @@ impl Command {
             utils::write_output(topic, path, &mut file, &contents)?;
         }
 
-        Ok(0)
+        Ok(Status::Success)
     }
 }
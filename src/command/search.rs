@@ -1,4 +1,4 @@
-use super::{Args, CmdResult, Error, State, StructOpt};
+use super::{Args, CmdResult, State, Status, StructOpt};
 
 #[derive(StructOpt, Debug)]
 pub struct Command {
@@ -19,8 +19,7 @@ impl Command {
             state
                 .schema_store
                 .find(&self.patterns, true, self.with_descriptions)
-        }
-        .ok_or(Error::Query)?;
+        }?;
 
         if !args.quiet {
             println!("Found {} schemas", list.len());
@@ -36,6 +35,10 @@ impl Command {
             }
         }
 
-        Ok(list.len() as u32)
+        Ok(if list.is_empty() {
+            Status::Failure
+        } else {
+            Status::Success
+        })
     }
 }
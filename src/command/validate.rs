@@ -1,126 +1,914 @@
 use super::{
-    utils, Args, CmdResult, CompiledSchema, Error, Format, Path, PathBuf, Standard, State,
-    StructOpt, Validator,
+    utils, Args, Branches, CmdResult, CompiledSchema, Error, Format, Inputs, OutputFormat, Path,
+    PathBuf, Report, Reporter, Result, Standard, State, Status, StructOpt, Totals, Validator,
 };
+use crate::{Resolver, Uri};
 
-#[derive(StructOpt, Debug)]
+use std::collections::HashMap;
+
+#[cfg(any(feature = "toml", feature = "yaml"))]
+use crate::Config;
+
+#[cfg(feature = "watch")]
+use crate::watch::{absolute, Watcher};
+#[cfg(feature = "watch")]
+use std::collections::BTreeSet;
+
+#[derive(StructOpt, Clone, Debug)]
 pub struct Command {
     /// Input data format
     #[structopt(short, long, possible_values = Format::LIST)]
     pub format: Option<Format>,
 
-    /// Using standard
-    #[structopt(short, long, possible_values = Standard::LIST)]
+    /// Using standard: draft4, draft6, draft7, draft2019-09 or draft2020-12 (detected from $schema by default)
+    #[structopt(short, long, possible_values = Standard::NAMES, hide_possible_values = true)]
     pub standard: Option<Standard>,
 
     /// Using validator
     #[structopt(short, long, default_value = Validator::LIST[0], possible_values = Validator::LIST)]
     pub validator: Validator,
 
+    /// Output format (structured formats prints one JSON document per input)
+    #[structopt(short, long, default_value = "text", possible_values = OutputFormat::LIST)]
+    pub output_format: OutputFormat,
+
+    /// Skip checking schema against meta-schema
+    #[structopt(long)]
+    pub no_schema_check: bool,
+
+    /// Validate only part of input documents at JSON Pointer (like `/spec/template`)
+    #[structopt(long)]
+    pub data_pointer: Option<String>,
+
+    /// Validate using subschema at JSON Pointer (like `/definitions/Container`)
+    #[structopt(long)]
+    pub schema_pointer: Option<String>,
+
+    /// Number of parallel jobs (0 means number of CPUs)
+    #[structopt(short, long, default_value = "1")]
+    pub jobs: usize,
+
+    #[cfg(feature = "watch")]
+    /// Watch schema and inputs and re-validate it on changes
+    #[structopt(short, long)]
+    pub watch: bool,
+
+    /// Select schemas for inputs by `$schema` property, modeline or schemastore file patterns (all arguments is inputs)
+    #[structopt(short, long)]
+    pub auto: bool,
+
     #[cfg(feature = "schemastore")]
-    /// Schema file or name
+    /// Schema file, URL or name (may be omitted when configuration maps files to schemas)
     #[structopt()]
-    pub schema: PathBuf,
+    pub schema: Option<PathBuf>,
 
     #[cfg(not(feature = "schemastore"))]
-    /// Schema file
+    /// Schema file or URL (may be omitted when configuration maps files to schemas)
     #[structopt()]
-    pub schema: PathBuf,
+    pub schema: Option<PathBuf>,
 
-    /// Data files to validate (otherwise data will be read from stdin)
-    #[structopt()]
-    pub input: Vec<PathBuf>,
+    #[structopt(flatten)]
+    pub inputs: Inputs,
 }
 
 impl Command {
-    pub fn run(&self, args: &Args, _state: &State) -> CmdResult {
-        let schema = if self.schema.is_file() {
-            let topic = "JSON Schema";
-            let path = &self.schema;
-            let mut file = utils::open_file(topic, path)?;
-            let data = utils::read_input(topic, path, &mut file)?;
-
-            Format::from_path(&self.schema)
-                .ok_or_else(|| {
-                    log::error!(
-                        "Unable to determine {} format from '{}'",
-                        topic,
-                        path.display()
-                    );
-                    Error::Parse
-                })?
-                .parse_data(topic, path, &data)
-                .ok_or(Error::Parse)?
-        } else {
+    pub fn run(&self, args: &Args, state: &State) -> CmdResult {
+        #[cfg(feature = "watch")]
+        if self.watch {
+            return self.watch(args, state);
+        }
+
+        if self.auto {
+            return self.run_auto(args, state);
+        }
+
+        #[cfg(any(feature = "toml", feature = "yaml"))]
+        if self.schema.is_none() {
+            match &state.config {
+                Ok(Some(config)) => return self.run_project(args, state, config),
+                Err(error) => return Err(Error::Usage(error.clone())),
+                Ok(None) => (),
+            }
+        }
+
+        let totals = self.validate_inputs(args, state)?;
+
+        self.print_totals(args, &totals);
+
+        Ok(Status::from_failures(totals.failed))
+    }
+
+    fn validate_inputs(&self, args: &Args, state: &State) -> Result<Totals> {
+        let prepared = self.load_checked(state)?;
+        let schema = self
+            .validator
+            .compile_schema(&prepared.schema, self.standard)?;
+        let branches = Branches::new(&schema, self.standard);
+        let reporter = self.reporter(args, &prepared, &schema, &branches);
+
+        self.inputs
+            .for_each("data", self.jobs, |topic, path, input| {
+                self.read_parse_check(args, topic, &schema, &reporter, path, input)
+            })
+    }
+
+    /// Validate project files using schemas from configuration
+    ///
+    /// Options of schema entries overrides options given by command-line.
+    #[cfg(any(feature = "toml", feature = "yaml"))]
+    fn run_project(&self, args: &Args, state: &State, config: &Config) -> CmdResult {
+        if config.schemas.is_empty() {
+            return Err(Error::Usage(format!(
+                "JSON Schema should be given because configuration from '{}' has no schemas",
+                config.dir.display()
+            )));
+        }
+
+        let mut totals = Totals::default();
+        for entry in &config.schemas {
+            let command = Self {
+                format: parse_entry(entry.format.as_deref(), "format")?.or(self.format),
+                standard: parse_entry(entry.standard.as_deref(), "standard")?.or(self.standard),
+                validator: parse_entry(entry.validator.as_deref(), "validator")?
+                    .unwrap_or(self.validator),
+                schema: Some(resolve_reference(&entry.schema, &config.dir)),
+                inputs: Inputs {
+                    include: entry.files.clone(),
+                    input: vec![config.dir.clone()],
+                    ..self.inputs.clone()
+                },
+                ..self.clone()
+            };
+            totals.merge(command.validate_inputs(args, state)?);
+        }
+
+        self.print_totals(args, &totals);
+
+        Ok(Status::from_failures(totals.failed))
+    }
+
+    /// Validate inputs using automatically selected schemas
+    ///
+    /// Schema referenced by document itself takes precedence over matched by schemastore file patterns.
+    fn run_auto(&self, args: &Args, state: &State) -> CmdResult {
+        #[cfg(feature = "schemastore")]
+        let matcher = state
+            .schema_store
+            .file_matcher()
+            .map_err(|error| {
+                log::warn!(
+                    "Unable to get schemas catalog from schemastore.org due to: {}",
+                    error
+                )
+            })
+            .ok();
+
+        // schema argument is an input too
+        let inputs = Inputs {
+            input: self
+                .schema
+                .iter()
+                .chain(&self.inputs.input)
+                .cloned()
+                .collect(),
+            ..self.inputs.clone()
+        };
+        if inputs.input.is_empty() {
+            return Err(Error::Usage(
+                "Input files should be given to select schemas for it".into(),
+            ));
+        }
+        let files = inputs.expand("data")?;
+
+        // documents isn't kept in memory so it's parsed again when validating
+        let references =
+            super::input::map_files(self.jobs, &files, |path| self.schema_reference(path));
+
+        // index of schema source for each input
+        let mut sources = Vec::new();
+        let mut selected = HashMap::new();
+        for (path, reference) in files.iter().zip(references) {
+            let source = reference.map(Source::Reference);
+
             #[cfg(feature = "schemastore")]
-            {
-                let pattern = self.schema.display().to_string();
-                _state
-                    .schema_store
-                    .get_one([&pattern], true, false)
-                    .ok_or_else(|| {
-                        log::error!(
-                            "JSON Schema '{}' not found nor in filesystem nor on schemastore.org",
-                            self.schema.display()
-                        );
-                        Error::Query
-                    })?
-                    .1
+            let source = source.or_else(|| {
+                let info = matcher.as_ref()?.find(path)?;
+                log::info!("Using JSON Schema '{}' for '{}'", info.name, path.display());
+                Some(Source::Catalog(info.url.as_ref().clone()))
+            });
+
+            if let Some(source) = source {
+                let index = sources
+                    .iter()
+                    .position(|other| *other == source)
+                    .unwrap_or_else(|| {
+                        sources.push(source);
+                        sources.len() - 1
+                    });
+                selected.insert(path.as_path(), index);
+            } else {
+                log::error!("No JSON Schema found for '{}'", path.display());
             }
+        }
 
-            #[cfg(not(feature = "schemastore"))]
-            {
-                log::error!(
-                    "JSON Schema '{}' not found in filesystem",
-                    self.schema.display()
-                );
-                return Err(Error::Query);
+        // schemas which cannot be loaded is reported and skipped
+        let schemas = sources
+            .iter()
+            .map(|source| {
+                match source {
+                    Source::Reference(path) => load_schema(path, state),
+                    #[cfg(feature = "schemastore")]
+                    Source::Catalog(url) => fetch_schema(url, state),
+                }
+                .and_then(|(schema, base)| self.prepare(state, &source.to_string(), schema, base))
+                .map_err(|error| log::error!("{}", error))
+                .ok()
+            })
+            .collect::<Vec<_>>();
+        let compiled = schemas
+            .iter()
+            .map(|prepared| {
+                self.validator
+                    .compile_schema(&prepared.as_ref()?.schema, self.standard)
+                    .map_err(|error| log::error!("{}", error))
+                    .ok()
+            })
+            .collect::<Vec<_>>();
+        let branches = compiled
+            .iter()
+            .map(|schema| Some(Branches::new(schema.as_ref()?, self.standard)))
+            .collect::<Vec<_>>();
+        let reporters = schemas
+            .iter()
+            .zip(&compiled)
+            .zip(&branches)
+            .map(|((prepared, compiled), branches)| {
+                Some(self.reporter(
+                    args,
+                    prepared.as_ref()?,
+                    compiled.as_ref()?,
+                    branches.as_ref()?,
+                ))
+            })
+            .collect::<Vec<_>>();
+
+        // inputs without usable schema is skipped and counted as failed
+        let usable = files
+            .iter()
+            .filter(|path| {
+                selected
+                    .get(path.as_path())
+                    .is_some_and(|index| compiled[*index].is_some())
+            })
+            .cloned()
+            .collect::<Vec<_>>();
+        let unprocessed = files.len() - usable.len();
+        let files = usable;
+        let mut totals =
+            super::input::process_files("data", self.jobs, &files, |topic, path, input| {
+                let index = selected[path];
+                let (schema, reporter) = compiled[index]
+                    .as_ref()
+                    .zip(reporters[index].as_ref())
+                    .expect("Only inputs with compiled schemas");
+                self.read_parse_check(args, topic, schema, reporter, path, input)
+            })?;
+        totals.add_unprocessed(unprocessed as u32);
+
+        self.print_totals(args, &totals);
+
+        Ok(Status::from_failures(totals.failed))
+    }
+
+    /// Find schema referenced by `$schema` property or modeline of data document
+    fn schema_reference(&self, path: &Path) -> Option<PathBuf> {
+        let topic = "data";
+        let input = utils::open_file(topic, path)
+            .and_then(|mut file| self.read_parse(topic, path, &mut file))
+            .map_err(|error| log::debug!("{}", error))
+            .ok()?;
+        let reference = find_schema_reference(&input.data, &input.documents)?;
+        log::info!(
+            "Using JSON Schema '{}' referenced by '{}'",
+            reference,
+            path.display()
+        );
+        let dir = path.parent().unwrap_or_else(|| Path::new(""));
+        Some(resolve_reference(&reference, dir))
+    }
+
+    /// Schema argument which is required unless schemas is selected automatically
+    fn schema(&self) -> Result<&Path> {
+        self.schema
+            .as_deref()
+            .ok_or_else(|| Error::Usage("JSON Schema should be given".into()))
+    }
+
+    /// Load schema, check it against meta-schema and apply custom formats
+    fn load_checked(&self, state: &State) -> Result<Prepared> {
+        let path = self.schema()?;
+        let (schema, base) = load_schema(path, state)?;
+        self.prepare(state, &path.display().to_string(), schema, base)
+    }
+
+    /// Check schema against meta-schema and apply custom formats
+    ///
+    /// Check is skipped with warning when no validator can compile meta-schema.
+    fn prepare(
+        &self,
+        state: &State,
+        name: &str,
+        schema: json::Value,
+        base: Option<Uri>,
+    ) -> Result<Prepared> {
+        if !self.no_schema_check {
+            // schema is still usable when it cannot be checked
+            let errors = self
+                .validator
+                .check_schema(&schema, self.standard)
+                .unwrap_or_else(|error| {
+                    log::warn!(
+                        "Unable to check JSON Schema '{}' against meta-schema: {}",
+                        name,
+                        error
+                    );
+                    Vec::new()
+                });
+            if !errors.is_empty() {
+                for error in &errors {
+                    log::error!("Invalid JSON Schema '{}': {}", name, error);
+                }
+                return Err(Error::Compile(format!(
+                    "JSON Schema '{}' is invalid. Use check-schema command to get details or --no-schema-check option to skip checking",
+                    name
+                )));
+            }
+        }
+
+        let (schema, root) = match self.schema_pointer() {
+            Some(pointer) => {
+                let subschema =
+                    Resolver::narrow(&schema, base.as_ref(), pointer).ok_or_else(|| {
+                        Error::Pointer {
+                            topic: "JSON Schema".into(),
+                            name: name.into(),
+                            pointer: pointer.into(),
+                        }
+                    })?;
+                (subschema, Some(schema))
             }
+            None => (schema, None),
         };
 
-        let schema = self.validator.compile_schema(&schema, self.standard)?;
+        Ok(Prepared {
+            schema: state.formats.apply(&schema),
+            base,
+            root,
+        })
+    }
 
-        let topic = "data";
+    /// Schema pointer without `#` prefix of URI fragment
+    fn schema_pointer(&self) -> Option<&str> {
+        let pointer = self.schema_pointer.as_deref()?;
+        Some(pointer.strip_prefix('#').unwrap_or(pointer))
+    }
 
-        if !self.input.is_empty() {
-            let mut errors = 0u32;
-            for path in &self.input {
-                if !path.is_file() {
-                    log::error!("Input {} file '{}' not found", topic, path.display());
-                    return Err(Error::Open);
+    /// Make reporter of data validated by compiled schema
+    ///
+    /// Keyword locations is reported relative to whole schema when it's narrowed.
+    fn reporter<'a>(
+        &'a self,
+        args: &Args,
+        prepared: &'a Prepared,
+        schema: &'a CompiledSchema,
+        branches: &'a Branches<'a>,
+    ) -> Reporter<'a> {
+        let root = prepared.root.as_ref().unwrap_or(schema.source());
+        let reporter = Reporter::new(self.output_format, args.verbose)
+            .with_schema(root, prepared.base.as_ref())
+            .with_best_matches(branches);
+        match self.schema_pointer() {
+            Some(pointer) => reporter.with_schema_pointer(pointer),
+            None => reporter,
+        }
+    }
+
+    fn print_totals(&self, args: &Args, totals: &Totals) {
+        if !args.quiet && self.output_format == OutputFormat::Text && totals.inputs > 1 {
+            println!(
+                "Checked {} inputs: {} valid, {} not valid, {} errors total",
+                totals.inputs,
+                totals.inputs - totals.failed,
+                totals.failed,
+                totals.errors
+            );
+        }
+    }
+
+    /// Validate inputs each time when schema or inputs changes
+    ///
+    /// All inputs is validated when schema or any referenced local file changes
+    /// otherwise only changed inputs is validated. Never returns on success.
+    #[cfg(feature = "watch")]
+    fn watch(&self, args: &Args, state: &State) -> CmdResult {
+        if self.auto {
+            return Err(Error::Usage(
+                "Watching inputs with automatically selected schemas isn't supported".into(),
+            ));
+        }
+        if self.inputs.input.is_empty() {
+            return Err(Error::Usage(
+                "Input files should be given to watch it".into(),
+            ));
+        }
+
+        let mut watcher = Watcher::new()?;
+
+        loop {
+            let mut schema_files = vec![absolute(self.schema()?)];
+            match self.load_checked(state) {
+                Ok(prepared) => {
+                    schema_files.extend(
+                        Resolver::bundled_files(&prepared.schema)
+                            .iter()
+                            .map(|path| absolute(path)),
+                    );
+                    watcher.watch(schema_files.iter().map(PathBuf::as_path))?;
+
+                    match self
+                        .validator
+                        .compile_schema(&prepared.schema, self.standard)
+                    {
+                        Ok(schema) => {
+                            let branches = Branches::new(&schema, self.standard);
+                            let reporter = self.reporter(args, &prepared, &schema, &branches);
+                            self.watch_inputs(
+                                args,
+                                &mut watcher,
+                                &schema,
+                                &reporter,
+                                &schema_files,
+                            )?;
+                            continue;
+                        }
+                        Err(error) => log::error!("{}", error),
+                    }
+                }
+                Err(error) => {
+                    log::error!("{}", error);
+                    watcher.watch(schema_files.iter().map(PathBuf::as_path))?;
                 }
-                let mut file = utils::open_file(topic, path)?;
-                errors += self.read_parse_check(args, topic, &schema, path, &mut file)?;
             }
-            Ok(errors)
-        } else {
-            let path = Path::new("stdin");
-            let mut file = std::io::stdin();
-            self.read_parse_check(args, topic, &schema, path, &mut file)
+
+            log::warn!("Waiting for changes of JSON Schema");
+            while !watcher
+                .wait()?
+                .iter()
+                .any(|path| schema_files.contains(path))
+            {}
         }
     }
 
+    /// Validate inputs on changes until schema changes
+    #[cfg(feature = "watch")]
+    fn watch_inputs(
+        &self,
+        args: &Args,
+        watcher: &mut Watcher,
+        schema: &CompiledSchema,
+        reporter: &Reporter,
+        schema_files: &[PathBuf],
+    ) -> Result<()> {
+        // changed files or none when all inputs should be validated
+        let mut changed: Option<BTreeSet<PathBuf>> = None;
+
+        loop {
+            // new files may appear in directories
+            let inputs = self
+                .inputs
+                .expand("data")
+                .map_err(|error| log::error!("{}", error))
+                .unwrap_or_default();
+            let selected = inputs
+                .iter()
+                .filter(|path| {
+                    changed
+                        .as_ref()
+                        .is_none_or(|changed| changed.contains(&absolute(path)))
+                })
+                .cloned()
+                .collect::<Vec<_>>();
+
+            if !selected.is_empty() {
+                match super::input::process_files(
+                    "data",
+                    self.jobs,
+                    &selected,
+                    |topic, path, input| {
+                        self.read_parse_check(args, topic, schema, reporter, path, input)
+                    },
+                ) {
+                    Ok(totals) => self.print_totals(args, &totals),
+                    Err(error) => log::error!("{}", error),
+                }
+            }
+
+            let dirs = self.inputs.input.iter().filter(|path| path.is_dir());
+            watcher.watch(inputs.iter().chain(dirs).map(PathBuf::as_path))?;
+            log::info!("Watching for changes");
+
+            let paths = watcher.wait()?;
+            if paths.iter().any(|path| schema_files.contains(path)) {
+                log::info!("JSON Schema is changed");
+                return Ok(());
+            }
+            changed = Some(paths);
+        }
+    }
+
+    fn read_parse(
+        &self,
+        topic: &str,
+        path: &Path,
+        input: &mut dyn std::io::Read,
+    ) -> Result<ParsedInput> {
+        let data = utils::read_input(topic, path, input)?;
+        let format = input_format(self.format, topic, path)?;
+        let documents = format.parse_documents(topic, path, &data)?;
+        Ok(ParsedInput {
+            data,
+            format,
+            documents,
+        })
+    }
+
     fn read_parse_check(
         &self,
         args: &Args,
         topic: &str,
         schema: &CompiledSchema,
+        reporter: &Reporter,
         path: &Path,
         input: &mut dyn std::io::Read,
-    ) -> CmdResult {
-        let data = utils::read_input(topic, path, input)?;
-        let format = self
-            .format
-            .or_else(|| Format::from_path(path))
-            .ok_or_else(|| {
-                log::error!(
-                    "Format of {} from '{}' is not given and cannot to be inferred from filename. Try use -f option to set it.",
-                    topic,
-                    path.display()
-                );
-                Error::Parse
-            })?;
-        let data = format.parse_data(topic, path, &data).ok_or(Error::Parse)?;
-        schema.validate_data(path, &data, args.quiet)
+    ) -> Result<Report> {
+        let input = self.read_parse(topic, path, input)?;
+        self.check(args, topic, schema, reporter, path, &input)
+    }
+
+    fn check(
+        &self,
+        args: &Args,
+        topic: &str,
+        schema: &CompiledSchema,
+        reporter: &Reporter,
+        path: &Path,
+        input: &ParsedInput,
+    ) -> Result<Report> {
+        let ParsedInput {
+            data,
+            format,
+            documents,
+        } = input;
+
+        // records of streams is reported as `path#index`
+        let by_records = format.is_stream() || documents.len() > 1;
+        let mut source_maps = None;
+        let mut report = String::new();
+        let mut count = 0;
+
+        for (index, document) in documents {
+            let name = if by_records {
+                PathBuf::from(format!("{}#{}", path.display(), index))
+            } else {
+                path.into()
+            };
+            // malformed records fails without stopping others
+            let whole = match document {
+                Ok(document) => document,
+                Err(error) => {
+                    log::error!("{}", error);
+                    count += 1;
+                    continue;
+                }
+            };
+            let document = match self.data_pointer.as_deref() {
+                Some(pointer) => resolve_pointer(whole, pointer).ok_or_else(|| Error::Pointer {
+                    topic: topic.into(),
+                    name: name.display().to_string(),
+                    pointer: pointer.into(),
+                })?,
+                None => whole,
+            };
+
+            // errors isn't collected when only validity is reported
+            if args.quiet || self.output_format == OutputFormat::Flag {
+                let valid = schema.is_valid(document)?;
+                count += u32::from(!valid);
+                if !args.quiet {
+                    report.push_str(&reporter.report_validity(&name, valid));
+                }
+                continue;
+            }
+
+            let mut errors = schema.validate_data(document)?;
+            if let Some(pointer) = self.data_pointer.as_deref() {
+                // locations is reported relative to whole document
+                for error in &mut errors {
+                    error
+                        .instance_path
+                        .insert_str(0, pointer.strip_prefix('#').unwrap_or(pointer));
+                }
+            }
+            count += errors.len() as u32;
+
+            let source_map = if !errors.is_empty() {
+                source_maps
+                    .get_or_insert_with(|| format.source_maps(data))
+                    .get(*index)
+            } else {
+                None
+            };
+            report.push_str(&reporter.report(&name, source_map, whole, &errors));
+        }
+
+        Ok((report, count))
+    }
+}
+
+/// Schema prepared for validation
+struct Prepared {
+    /// Schema narrowed by schema pointer with applied custom formats
+    schema: json::Value,
+    base: Option<Uri>,
+    /// Whole schema when it's narrowed
+    root: Option<json::Value>,
+}
+
+/// Data input with parsed documents
+struct ParsedInput {
+    data: Vec<u8>,
+    format: Format,
+    /// Documents with its indexes in stream
+    documents: Vec<(usize, Result<json::Value>)>,
+}
+
+/// Source of automatically selected schema
+#[derive(PartialEq, Eq)]
+enum Source {
+    /// Schema file, URL or name referenced by document
+    Reference(PathBuf),
+    /// Schema URL from schemastore catalog
+    #[cfg(feature = "schemastore")]
+    Catalog(Uri),
+}
+
+impl std::fmt::Display for Source {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::Reference(path) => path.display().fmt(f),
+            #[cfg(feature = "schemastore")]
+            Self::Catalog(url) => url.fmt(f),
+        }
+    }
+}
+
+/// Find schema reference in data document
+///
+/// Modelines like `# yaml-language-server: $schema=<ref>` or `#:schema <ref>`
+/// in leading comments takes precedence over top-level `$schema` property.
+fn find_schema_reference(
+    data: &[u8],
+    documents: &[(usize, Result<json::Value>)],
+) -> Option<String> {
+    let modeline = std::str::from_utf8(data).ok().and_then(|text| {
+        text.lines()
+            .map(str::trim)
+            .take_while(|line| line.is_empty() || line.starts_with('#'))
+            .find_map(|line| {
+                let comment = line.strip_prefix('#')?.trim();
+                comment
+                    .strip_prefix("yaml-language-server:")
+                    .and_then(|options| options.trim_start().strip_prefix("$schema="))
+                    .or_else(|| comment.strip_prefix(":schema "))
+                    .map(|reference| reference.trim().to_string())
+            })
+    });
+    modeline.or_else(|| {
+        let (_, document) = documents.first()?;
+        let document = document.as_ref().ok()?;
+        Some(document.get("$schema")?.as_str()?.into())
+    })
+}
+
+/// Get value at JSON Pointer which may be given as URI fragment (`#/a/b`)
+fn resolve_pointer<'v>(value: &'v json::Value, pointer: &str) -> Option<&'v json::Value> {
+    value.pointer(pointer.strip_prefix('#').unwrap_or(pointer))
+}
+
+/// Resolve schema reference relative to directory
+///
+/// URLs and names which isn't existing files is used as is.
+fn resolve_reference(reference: &str, dir: &Path) -> PathBuf {
+    if parse_url(reference).is_none() {
+        let path = dir.join(reference);
+        if path.is_file() {
+            return path;
+        }
+    }
+    reference.into()
+}
+
+/// Parse option of configuration entry
+#[cfg(any(feature = "toml", feature = "yaml"))]
+fn parse_entry<T: std::str::FromStr>(value: Option<&str>, option: &str) -> Result<Option<T>> {
+    value
+        .map(|value| {
+            value.parse().map_err(|_| {
+                Error::Usage(format!("Unknown {} '{}' in configuration", option, value))
+            })
+        })
+        .transpose()
+}
+
+/// Parse absolute URL
+///
+/// Single-letter schemes is rejected because it may be a drive letter.
+fn parse_url(reference: &str) -> Option<Uri> {
+    reference
+        .parse::<Uri>()
+        .ok()
+        .filter(|url| url.scheme().len() > 1)
+}
+
+/// Load schema from file, URL or schemastore and bundle external references
+///
+/// Returns bundled schema with its base URI.
+pub fn load_schema(schema: &Path, state: &State) -> Result<(json::Value, Option<Uri>)> {
+    let (schema, base) = if schema.is_file() {
+        let topic = "JSON Schema";
+        let path = schema;
+        let mut file = utils::open_file(topic, path)?;
+        let data = utils::read_input(topic, path, &mut file)?;
+        let uri = path
+            .canonicalize()
+            .ok()
+            .and_then(|path| Uri::from_file_path(path).ok());
+
+        let schema = Format::from_path(path)
+            .ok_or_else(|| Error::UnknownFormat {
+                topic: topic.into(),
+                path: path.into(),
+            })?
+            .parse_data(topic, path, &data)
+            .map_err(Error::into_schema)?;
+
+        (schema, uri)
+    } else if let Some(url) = schema.to_str().and_then(parse_url) {
+        let schema = state.resolver.fetch(&url)?;
+        (schema, Some(url))
+    } else {
+        #[cfg(feature = "schemastore")]
+        {
+            let pattern = schema.display().to_string();
+            let (info, schema) = state.schema_store.get_one([&pattern], true, false)?;
+            (schema, Some(info.url.as_ref().clone()))
+        }
+
+        #[cfg(not(feature = "schemastore"))]
+        {
+            return Err(Error::NotFound(schema.display().to_string()));
+        }
+    };
+
+    Ok(bundle_schema(schema, base, state))
+}
+
+/// Fetch schema from schemastore by URL and bundle external references
+#[cfg(feature = "schemastore")]
+pub fn fetch_schema(url: &Uri, state: &State) -> Result<(json::Value, Option<Uri>)> {
+    let schema = state.schema_store.get_by_url(url)?;
+    Ok(bundle_schema(schema, Some(url.clone()), state))
+}
+
+fn bundle_schema(
+    schema: json::Value,
+    base: Option<Uri>,
+    state: &State,
+) -> (json::Value, Option<Uri>) {
+    let schema = state.resolver.bundle(schema, base.as_ref());
+
+    // identifier of schema takes precedence over its location
+    let base = schema_id(&schema).or(base);
+
+    (schema, base)
+}
+
+/// Read and parse input data
+///
+/// Returns raw data, its format and parsed value.
+pub fn read_parse(
+    format: Option<Format>,
+    topic: &str,
+    path: &Path,
+    input: &mut dyn std::io::Read,
+) -> Result<(Vec<u8>, Format, json::Value)> {
+    let data = utils::read_input(topic, path, input)?;
+    let format = input_format(format, topic, path)?;
+    let value = format.parse_data(topic, path, &data)?;
+    Ok((data, format, value))
+}
+
+/// Get given input format or infer it from path
+fn input_format(format: Option<Format>, topic: &str, path: &Path) -> Result<Format> {
+    format
+        .or_else(|| Format::from_path(path))
+        .ok_or_else(|| Error::UnknownFormat {
+            topic: topic.into(),
+            path: path.into(),
+        })
+}
+
+fn schema_id(schema: &json::Value) -> Option<Uri> {
+    // draft4 uses `id` instead of `$id`
+    schema
+        .get("$id")
+        .or_else(|| schema.get("id"))?
+        .as_str()?
+        .parse()
+        .ok()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn schema_references() {
+        let path = Path::new("data");
+        let find = |format: Format, data: &str| {
+            let documents = format
+                .parse_documents("data", path, data.as_bytes())
+                .unwrap_or_default();
+            find_schema_reference(data.as_bytes(), &documents)
+        };
+
+        assert_eq!(
+            find(
+                Format::Json,
+                r#"{ "$schema": "https://example.com/schema.json", "a": 1 }"#
+            ),
+            Some("https://example.com/schema.json".into())
+        );
+        assert_eq!(find(Format::Json, r#"{ "a": 1 }"#), None);
+        #[cfg(feature = "yaml")]
+        assert_eq!(
+            find(
+                Format::Yaml,
+                "# yaml-language-server: $schema=../schemas/app.json\na: 1\n"
+            ),
+            Some("../schemas/app.json".into())
+        );
+        #[cfg(feature = "toml")]
+        assert_eq!(
+            find(Format::Toml, "#:schema ./config.schema.json\na = 1\n"),
+            Some("./config.schema.json".into())
+        );
+        // modelines is looked in leading comments only
+        #[cfg(feature = "yaml")]
+        assert_eq!(
+            find(
+                Format::Yaml,
+                "a: |\n  # yaml-language-server: $schema=other.json\n"
+            ),
+            None
+        );
+
+        assert_eq!(
+            resolve_reference("https://example.com/schema.json", path),
+            PathBuf::from("https://example.com/schema.json")
+        );
+        assert_eq!(
+            resolve_reference("github-workflow", path),
+            PathBuf::from("github-workflow")
+        );
+        assert_eq!(
+            resolve_reference("Cargo.toml", Path::new("src/..")),
+            PathBuf::from("src/../Cargo.toml")
+        );
+    }
+
+    #[test]
+    fn pointers() {
+        let data = json::json!({ "spec": { "template": { "a": 1 } } });
+        assert_eq!(
+            resolve_pointer(&data, "/spec/template"),
+            Some(&json::json!({ "a": 1 }))
+        );
+        assert_eq!(
+            resolve_pointer(&data, "#/spec/template/a"),
+            Some(&json::json!(1))
+        );
+        assert_eq!(resolve_pointer(&data, "#"), Some(&data));
+        assert_eq!(resolve_pointer(&data, "/status"), None);
     }
 }
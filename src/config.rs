@@ -0,0 +1,278 @@
+/*!
+
+Project configuration file discovered from current directory upward
+
+*/
+
+use crate::Args;
+#[cfg(any(
+    feature = "jsonschema",
+    feature = "jsonschema-valid",
+    feature = "valico"
+))]
+use crate::FormatMode;
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+use structopt::clap::ArgMatches;
+
+/// Names of configuration files in order of precedence
+const CONFIG_NAMES: &[&str] = &[
+    #[cfg(feature = "toml")]
+    "jsonst.toml",
+    #[cfg(feature = "toml")]
+    ".jsonst.toml",
+    #[cfg(feature = "yaml")]
+    "jsonst.yaml",
+    #[cfg(feature = "yaml")]
+    ".jsonst.yaml",
+];
+
+/// Project configuration
+///
+/// Relative paths is resolved against directory of configuration file.
+#[derive(Deserialize, Default, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// Directory of configuration file relative to current directory
+    #[serde(skip)]
+    pub dir: PathBuf,
+
+    /// Logging level
+    pub log_level: Option<String>,
+
+    /// Cache directory
+    pub cache_dir: Option<PathBuf>,
+
+    /// Schema store catalog url
+    pub catalog_url: Option<String>,
+
+    /// Directories with local schemas which is referenced by its ids
+    #[serde(default)]
+    pub schema_dir: Vec<PathBuf>,
+
+    /// Files with custom formats
+    #[serde(default)]
+    pub formats: Vec<PathBuf>,
+
+    /// Treatment of format keyword
+    pub format_mode: Option<String>,
+
+    /// Schemas of project files
+    #[serde(default)]
+    pub schemas: Vec<SchemaEntry>,
+}
+
+/// Mapping of files to schema
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct SchemaEntry {
+    /// Glob patterns of files (relative to configuration directory)
+    #[serde(alias = "fileMatch")]
+    pub files: Vec<String>,
+
+    /// Schema file, URL or schemastore name
+    #[serde(alias = "url")]
+    pub schema: String,
+
+    /// Validator to use
+    pub validator: Option<String>,
+
+    /// Standard to use
+    pub standard: Option<String>,
+
+    /// Format of files
+    pub format: Option<String>,
+}
+
+impl Config {
+    /// Find configuration file in current directory or its ancestors
+    ///
+    /// Logging isn't initialized yet so errors is returned as messages.
+    pub fn discover() -> Result<Option<Self>, String> {
+        let cwd = std::env::current_dir()
+            .map_err(|error| format!("Unable to get current directory due to: {}", error))?;
+        for (level, ancestor) in cwd.ancestors().enumerate() {
+            for name in CONFIG_NAMES {
+                let path = ancestor.join(name);
+                if path.is_file() {
+                    let dir = if level == 0 {
+                        PathBuf::from(".")
+                    } else {
+                        (0..level).map(|_| "..").collect()
+                    };
+                    return Self::load(&path, dir).map(Some);
+                }
+            }
+        }
+        Ok(None)
+    }
+
+    fn load(path: &Path, dir: PathBuf) -> Result<Self, String> {
+        let data = std::fs::read(path).map_err(|error| {
+            format!(
+                "Unable to read configuration '{}' due to: {}",
+                path.display(),
+                error
+            )
+        })?;
+        let config: std::result::Result<Self, String> =
+            match path.extension().and_then(|ext| ext.to_str()) {
+                #[cfg(feature = "toml")]
+                Some("toml") => toml::from_slice(&data).map_err(|error| error.to_string()),
+                #[cfg(feature = "yaml")]
+                Some("yaml") => yaml::from_slice(&data).map_err(|error| error.to_string()),
+                _ => Err("unsupported format".into()),
+            };
+        let mut config = config.map_err(|error| {
+            format!(
+                "Invalid configuration '{}' due to: {}",
+                path.display(),
+                error
+            )
+        })?;
+        config.dir = dir;
+        Ok(config)
+    }
+
+    /// Resolve path relative to configuration directory
+    pub fn path(&self, path: &Path) -> PathBuf {
+        self.dir.join(path)
+    }
+
+    /// Apply options which isn't given by command-line or environment
+    ///
+    /// Options is checked before applying so arguments is left untouched on errors.
+    pub fn apply(&self, args: &mut Args, matches: &ArgMatches) -> Result<(), String> {
+        let invalid = |option: &str, value: &str| {
+            format!(
+                "Invalid {} '{}' in configuration from '{}'",
+                option,
+                value,
+                self.dir.display()
+            )
+        };
+
+        let log_level = match &self.log_level {
+            Some(level) if !crate::command::LOG_LEVELS.contains(&level.as_str()) => {
+                return Err(invalid("log_level", level))
+            }
+            level => level.clone(),
+        };
+        #[cfg(feature = "schemastore")]
+        let catalog_url = self
+            .catalog_url
+            .as_deref()
+            .map(|url| url.parse().map_err(|_| invalid("catalog_url", url)))
+            .transpose()?;
+        #[cfg(any(
+            feature = "jsonschema",
+            feature = "jsonschema-valid",
+            feature = "valico"
+        ))]
+        let format_mode = self
+            .format_mode
+            .as_deref()
+            .map(|mode| {
+                mode.parse::<FormatMode>()
+                    .map_err(|_| invalid("format_mode", mode))
+            })
+            .transpose()?;
+
+        if let Some(level) = log_level.filter(|_| !is_given(matches, "log-level", "LOG_LEVEL")) {
+            args.log_level = level;
+        }
+        #[cfg(feature = "cache")]
+        if let Some(path) = self
+            .cache_dir
+            .as_ref()
+            .filter(|_| !is_given(matches, "cache-dir", "CACHE_DIR"))
+        {
+            args.cache_dir = Some(self.path(path));
+        }
+        #[cfg(feature = "schemastore")]
+        if let Some(url) = catalog_url.filter(|_| !is_given(matches, "catalog-url", "CATALOG_URL"))
+        {
+            args.catalog_url = url;
+        }
+        #[cfg(any(
+            feature = "jsonschema",
+            feature = "jsonschema-valid",
+            feature = "valico"
+        ))]
+        {
+            if let Some(mode) =
+                format_mode.filter(|_| !is_given(matches, "format-mode", "FORMAT_MODE"))
+            {
+                args.format_mode = mode;
+            }
+            // lists from configuration is used only when it isn't given
+            if args.schema_dir.is_empty() {
+                args.schema_dir = self.schema_dir.iter().map(|path| self.path(path)).collect();
+            }
+            if args.formats.is_empty() {
+                args.formats = self.formats.iter().map(|path| self.path(path)).collect();
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Check that option is given by command-line or environment variable
+fn is_given(matches: &ArgMatches, name: &str, var: &str) -> bool {
+    matches.occurrences_of(name) > 0 || std::env::var_os(var).is_some()
+}
+
+#[cfg(all(test, feature = "toml"))]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse_config() {
+        let config: Config = toml::from_str(
+            r#"
+log_level = "info"
+cache_dir = ".cache"
+schema_dir = ["schemas"]
+
+[[schemas]]
+files = ["deploy/**/*.yaml"]
+schema = "schemas/deploy.json"
+validator = "jsonschema"
+
+[[schemas]]
+fileMatch = ["package.json"]
+url = "package"
+"#,
+        )
+        .unwrap();
+        assert_eq!(config.log_level.as_deref(), Some("info"));
+        assert_eq!(config.schemas.len(), 2);
+        assert_eq!(config.schemas[1].files, ["package.json"]);
+        assert_eq!(config.schemas[1].schema, "package");
+        assert!(toml::from_str::<Config>("log_levle = \"info\"").is_err());
+    }
+
+    #[test]
+    fn apply_config() {
+        use structopt::StructOpt;
+
+        let parse = |argv: &[&str]| {
+            let matches = Args::clap().get_matches_from_safe(argv).unwrap();
+            (Args::from_clap(&matches), matches)
+        };
+        let config: Config = toml::from_str("log_level = \"debug\"").unwrap();
+
+        let (mut args, matches) = parse(&["jsonst", "validate"]);
+        config.apply(&mut args, &matches).unwrap();
+        assert_eq!(args.log_level, "debug");
+
+        // command-line takes precedence
+        let (mut args, matches) = parse(&["jsonst", "-l", "error", "validate"]);
+        config.apply(&mut args, &matches).unwrap();
+        assert_eq!(args.log_level, "error");
+
+        let config: Config = toml::from_str("log_level = \"loud\"").unwrap();
+        assert!(config.apply(&mut args, &matches).is_err());
+        assert_eq!(args.log_level, "error");
+    }
+}
@@ -0,0 +1,373 @@
+/*!
+
+Errors with context of failed operations
+
+*/
+
+use crate::Format;
+use std::{
+    fmt::{Display, Formatter, Result as FmtResult},
+    io::Error as IoError,
+    path::PathBuf,
+};
+
+/// Boxed underlying error
+pub type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+/// Errors
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// Unable to open file
+    Open {
+        topic: String,
+        path: PathBuf,
+        source: IoError,
+    },
+    /// Unable to read data
+    Read {
+        topic: String,
+        path: PathBuf,
+        source: IoError,
+    },
+    /// Unable to create file
+    Create {
+        topic: String,
+        path: PathBuf,
+        source: IoError,
+    },
+    /// Unable to write data
+    Write {
+        topic: String,
+        path: PathBuf,
+        source: IoError,
+    },
+    /// Output file cannot be overwritten
+    Conflict { path: PathBuf, reason: &'static str },
+    /// Unable to serialize data
+    Format { topic: String, source: json::Error },
+    /// Unable to serialize data in format
+    Serialize {
+        topic: String,
+        format: Format,
+        source: BoxError,
+    },
+    /// Unable to parse data
+    ///
+    /// Schema is set when parsed data is JSON Schema.
+    Parse {
+        topic: String,
+        format: Format,
+        path: PathBuf,
+        source: BoxError,
+        schema: bool,
+    },
+    /// Format of data isn't given and cannot be inferred from path
+    UnknownFormat { topic: String, path: PathBuf },
+    /// Invalid glob or regex pattern
+    Pattern { pattern: String, source: BoxError },
+    /// Invalid arguments or configuration
+    Usage(String),
+    /// Unable to fetch remote document
+    Fetch { url: String, source: BoxError },
+    /// Schema not found
+    NotFound(String),
+    /// Schema cannot be compiled
+    Compile(String),
+    /// JSON Pointer doesn't point to any value of document
+    Pointer {
+        topic: String,
+        name: String,
+        pointer: String,
+    },
+    /// Unable to watch files for changes
+    #[cfg(feature = "watch")]
+    Watch {
+        path: Option<PathBuf>,
+        source: notify::Error,
+    },
+}
+
+impl Error {
+    /// Exit code for invalid command-line arguments or configuration
+    pub const USAGE: i32 = 2;
+    /// Exit code for errors of reading, parsing or writing files
+    pub const IO: i32 = 3;
+    /// Exit code for errors of fetching from network
+    pub const NETWORK: i32 = 4;
+    /// Exit code for invalid or unusable schemas
+    pub const SCHEMA: i32 = 5;
+
+    /// Get stable code of error
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::Open { .. } => "open",
+            Self::Read { .. } => "read",
+            Self::Create { .. } => "create",
+            Self::Write { .. } => "write",
+            Self::Conflict { .. } => "conflict",
+            Self::Format { .. } => "format",
+            Self::Serialize { .. } => "serialize",
+            Self::Parse { .. } => "parse",
+            Self::UnknownFormat { .. } => "unknown-format",
+            Self::Pattern { .. } => "pattern",
+            Self::Usage(_) => "usage",
+            Self::Fetch { .. } => "fetch",
+            Self::NotFound(_) => "not-found",
+            Self::Compile(_) => "compile",
+            Self::Pointer { .. } => "pointer",
+            #[cfg(feature = "watch")]
+            Self::Watch { .. } => "watch",
+        }
+    }
+
+    /// Get process exit code of error
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            // schema which cannot be parsed is unusable too
+            Self::Parse { schema: true, .. } => Self::SCHEMA,
+            Self::Conflict { .. }
+            | Self::UnknownFormat { .. }
+            | Self::Pattern { .. }
+            | Self::Usage(_)
+            | Self::NotFound(_)
+            | Self::Pointer { .. } => Self::USAGE,
+            Self::Open { .. }
+            | Self::Read { .. }
+            | Self::Create { .. }
+            | Self::Write { .. }
+            | Self::Format { .. }
+            | Self::Serialize { .. }
+            | Self::Parse { .. } => Self::IO,
+            #[cfg(feature = "watch")]
+            Self::Watch { .. } => Self::IO,
+            Self::Fetch { .. } => Self::NETWORK,
+            Self::Compile(_) => Self::SCHEMA,
+        }
+    }
+
+    /// Get path of file related to error
+    pub fn path(&self) -> Option<&std::path::Path> {
+        match self {
+            Self::Open { path, .. }
+            | Self::Read { path, .. }
+            | Self::Create { path, .. }
+            | Self::Write { path, .. }
+            | Self::Conflict { path, .. }
+            | Self::Parse { path, .. }
+            | Self::UnknownFormat { path, .. } => Some(path),
+            #[cfg(feature = "watch")]
+            Self::Watch { path, .. } => path.as_deref(),
+            _ => None,
+        }
+    }
+
+    /// Get URL of remote document related to error
+    pub fn url(&self) -> Option<&str> {
+        match self {
+            Self::Fetch { url, .. } => Some(url),
+            _ => None,
+        }
+    }
+
+    /// Get format of data related to error
+    pub fn format(&self) -> Option<Format> {
+        match self {
+            Self::Serialize { format, .. } | Self::Parse { format, .. } => Some(*format),
+            _ => None,
+        }
+    }
+
+    /// Mark error of parsing data as error of parsing JSON Schema
+    pub fn into_schema(self) -> Self {
+        match self {
+            Self::Parse {
+                topic,
+                format,
+                path,
+                source,
+                ..
+            } => Self::Parse {
+                topic,
+                format,
+                path,
+                source,
+                schema: true,
+            },
+            error => error,
+        }
+    }
+
+    /// Create error of fetching remote document
+    pub fn fetch(url: impl Into<String>, source: impl Into<BoxError>) -> Self {
+        Self::Fetch {
+            url: url.into(),
+            source: source.into(),
+        }
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        match self {
+            Self::Open {
+                topic,
+                path,
+                source,
+            } => write!(
+                f,
+                "Unable to open {} file '{}' due to: {}",
+                topic,
+                path.display(),
+                source
+            ),
+            Self::Read {
+                topic,
+                path,
+                source,
+            } => write!(
+                f,
+                "Unable to read {} from '{}' due to: {}",
+                topic,
+                path.display(),
+                source
+            ),
+            Self::Create {
+                topic,
+                path,
+                source,
+            } => write!(
+                f,
+                "Unable to create {} file '{}' due to: {}",
+                topic,
+                path.display(),
+                source
+            ),
+            Self::Write {
+                topic,
+                path,
+                source,
+            } => write!(
+                f,
+                "Unable to write {} to '{}' due to: {}",
+                topic,
+                path.display(),
+                source
+            ),
+            Self::Conflict { path, reason } => {
+                write!(f, "Output path '{}' {}", path.display(), reason)
+            }
+            Self::Format { topic, source } => {
+                write!(f, "Unable to format {} due to: {}", topic, source)
+            }
+            Self::Serialize {
+                topic,
+                format,
+                source,
+            } => write!(f, "Unable to serialize {} as {} due to: {}", topic, format, source),
+            Self::Parse {
+                topic,
+                format,
+                path,
+                source,
+                ..
+            } => write!(
+                f,
+                "Unable to parse {} {} from '{}' due to: {}",
+                format,
+                topic,
+                path.display(),
+                source
+            ),
+            Self::UnknownFormat { topic, path } => write!(
+                f,
+                "Format of {} from '{}' is not given and cannot to be inferred from filename. Try use -f option to set it.",
+                topic,
+                path.display()
+            ),
+            Self::Pattern { pattern, source } => {
+                write!(f, "Invalid pattern '{}' due to: {}", pattern, source)
+            }
+            Self::Usage(message) | Self::Compile(message) => message.fmt(f),
+            Self::Fetch { url, source } => {
+                write!(f, "Unable to fetch '{}' due to: {}", url, source)
+            }
+            Self::NotFound(name) => write!(f, "JSON Schema '{}' not found", name),
+            Self::Pointer {
+                topic,
+                name,
+                pointer,
+            } => write!(
+                f,
+                "JSON Pointer '{}' doesn't point to any value of {} '{}'",
+                pointer, topic, name
+            ),
+            #[cfg(feature = "watch")]
+            Self::Watch {
+                path: Some(path),
+                source,
+            } => write!(f, "Unable to watch '{}' due to: {}", path.display(), source),
+            #[cfg(feature = "watch")]
+            Self::Watch { path: None, source } => {
+                write!(f, "Unable to watch changes due to: {}", source)
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Open { source, .. }
+            | Self::Read { source, .. }
+            | Self::Create { source, .. }
+            | Self::Write { source, .. } => Some(source),
+            #[cfg(feature = "watch")]
+            Self::Watch { source, .. } => Some(source),
+            Self::Format { source, .. } => Some(source),
+            Self::Serialize { source, .. }
+            | Self::Parse { source, .. }
+            | Self::Pattern { source, .. }
+            | Self::Fetch { source, .. } => Some(source.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+/// Result alias
+pub type Result<T> = std::result::Result<T, Error>;
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::{error::Error as _, path::Path};
+
+    #[test]
+    fn parse_error_context() {
+        let error = Format::Json
+            .parse_data("data", Path::new("dir/data.json"), b"{\"a\": ")
+            .unwrap_err();
+        assert_eq!(error.code(), "parse");
+        assert_eq!(error.exit_code(), Error::IO);
+        assert_eq!(error.path(), Some(Path::new("dir/data.json")));
+        assert_eq!(error.format(), Some(Format::Json));
+        assert!(error.source().is_some());
+        assert!(error
+            .to_string()
+            .starts_with("Unable to parse json data from 'dir/data.json' due to: "));
+    }
+
+    #[test]
+    fn exit_codes() {
+        let error = Format::Json
+            .parse_data("JSON Schema", Path::new("schema.json"), b"{")
+            .unwrap_err();
+        assert_eq!(error.exit_code(), Error::IO);
+        assert_eq!(error.into_schema().exit_code(), Error::SCHEMA);
+        assert_eq!(
+            Error::NotFound("app".into()).into_schema().exit_code(),
+            Error::USAGE
+        );
+        assert_eq!(Error::NotFound("app".into()).exit_code(), Error::USAGE);
+    }
+}
@@ -4,8 +4,8 @@ Embedded HTTP client with support for redirects and caching
 
 */
 
-use crate::Cache;
-use http_req::{error::Error, request::Request, response::Response, uri::Uri};
+use crate::{Cache, Error, Result};
+use http_req::{request::Request, response::Response, uri::Uri};
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use std::{
     convert::TryInto,
@@ -40,7 +40,25 @@ impl<T> Doc<T> {
     }
 }
 
-pub fn get_cached<T>(cache: &Cache, url: impl AsRef<str>) -> Option<T>
+pub fn get_cached<T>(cache: &Cache, url: impl AsRef<str>) -> Result<T>
+where
+    T: Serialize + DeserializeOwned,
+{
+    let url = url.as_ref();
+    get_cached_with(cache, url, UPDATE_INTERVAL, |_, body| {
+        json::from_slice(body).map_err(|error| Error::fetch(url, error))
+    })
+}
+
+/// Fetch from HTTP using cache with specified update interval
+///
+/// Body is parsed using its content type when it isn't cached yet.
+pub fn get_cached_with<T>(
+    cache: &Cache,
+    url: impl AsRef<str>,
+    update_interval: Duration,
+    parse: impl FnOnce(Option<&str>, &[u8]) -> Result<T>,
+) -> Result<T>
 where
     T: Serialize + DeserializeOwned,
 {
@@ -48,9 +66,9 @@ where
 
     let cached_doc: Option<Doc<T>> = cache.get(&url);
     if let Some(cached_doc) = cached_doc {
-        if cached_doc.time.elapsed().unwrap_or(UPDATE_INTERVAL) < UPDATE_INTERVAL {
+        if cached_doc.time.elapsed().unwrap_or(update_interval) < update_interval {
             // prevent too often requests
-            return Some(cached_doc.body);
+            return Ok(cached_doc.body);
         }
         match check(url, &cached_doc.etag, &cached_doc.date) {
             // when error happened or not modified
@@ -60,24 +78,26 @@ where
                 cached_doc.refresh();
                 cache.put(&url, &cached_doc);
 
-                return Some(cached_doc.body);
+                return Ok(cached_doc.body);
             }
             _ => (),
         }
     }
 
-    let doc = get(url)?;
+    let doc = get(url, parse)?;
 
     cache.put(&url, &doc);
 
-    Some(doc.body)
+    Ok(doc.body)
 }
 
 /// Fetch from HTTP
-pub fn get<T>(url: impl AsRef<str>) -> Option<Doc<T>>
-where
-    T: DeserializeOwned,
-{
+///
+/// Body is parsed using value of `Content-Type` header if any.
+pub fn get<T>(
+    url: impl AsRef<str>,
+    parse: impl FnOnce(Option<&str>, &[u8]) -> Result<T>,
+) -> Result<Doc<T>> {
     let url = url.as_ref();
     let mut body = Vec::new();
 
@@ -97,23 +117,17 @@ where
     if res.status_code().is_success() {
         let etag = res.headers().get("ETag").cloned();
         let date = res.headers().get("Last-Modified").cloned();
+        let content_type = res.headers().get("Content-Type").map(String::as_str);
 
-        let body = json::from_slice(&body)
-            .map_err(|error| {
-                log::error!(
-                    "Unable to parse json document from '{}' due to: {}",
-                    url,
-                    error
-                );
-            })
-            .ok()?;
-
-        return Some(Doc::new(body, etag, date));
-    }
+        let body = parse(content_type, &body)?;
 
-    log::warn!("Unexpected HTTP response: {}", res.status_code());
+        return Ok(Doc::new(body, etag, date));
+    }
 
-    None
+    Err(Error::fetch(
+        url,
+        format!("unexpected HTTP response: {}", res.status_code()),
+    ))
 }
 
 /// Check HTTP resource for updates
@@ -137,7 +151,9 @@ pub fn check(
             req.header("If-Modified-Since", date.as_ref());
         }
         req.send(&mut body)
-    })?;
+    })
+    .map_err(|error| log::warn!("{}", error))
+    .ok()?;
 
     log::info!(
         "Received HTTP HEAD response from: {} with status: {}",
@@ -158,10 +174,10 @@ pub fn check(
     None
 }
 
-fn with_redirects<F: FnMut(&Uri) -> Result<Response, Error>>(
+fn with_redirects<F: FnMut(&Uri) -> std::result::Result<Response, http_req::error::Error>>(
     url: &str,
     mut mk_req: F,
-) -> Option<Response> {
+) -> Result<Response> {
     let mut right_redirects = REDIRECT_LIMIT;
 
     let mut url = url.to_string();
@@ -170,27 +186,15 @@ fn with_redirects<F: FnMut(&Uri) -> Result<Response, Error>>(
         let urlp = url
             .as_str()
             .try_into()
-            .map_err(|error| {
-                if right_redirects < REDIRECT_LIMIT {
-                    log::warn!("Invalid redirect url '{}' due to: {}", url, error)
-                } else {
-                    log::warn!("Invalid url '{}' due to: {}", url, error)
-                }
-            })
-            .ok()?;
-
-        let res = mk_req(&urlp)
-            .map_err(|error| {
-                log::warn!("Invalid request '{}' due to: {}", url.to_string(), error);
-            })
-            .ok()?;
-
-        if res.status_code().is_redirect() {
-            if right_redirects == 0 {
-                break;
-            }
-        } else {
-            return Some(res);
+            .map_err(|error| Error::fetch(&url, error))?;
+
+        let res = mk_req(&urlp).map_err(|error| Error::fetch(&url, error))?;
+
+        if !res.status_code().is_redirect() {
+            return Ok(res);
+        }
+        if right_redirects == 0 {
+            return Err(Error::fetch(url, "too many redirects"));
         }
 
         right_redirects -= 1;
@@ -198,9 +202,7 @@ fn with_redirects<F: FnMut(&Uri) -> Result<Response, Error>>(
         if let Some(location) = res.headers().get("Location") {
             url = location.into();
         } else {
-            break;
+            return Err(Error::fetch(url, "redirect without location"));
         }
     }
-
-    None
 }
@@ -0,0 +1,64 @@
+/*!
+
+JSON Schema hacking toolset library
+
+Parsing data of many formats with source locations, searching and retrieving
+schemas on [schemastore.org](https://schemastore.org/) and validating data
+using multiple validators.
+
+```
+use jsonst::{Format, Validator};
+
+let schema = Format::Json
+    .parse_data("JSON Schema", "schema.json".as_ref(), br#"{"type": "string"}"#)
+    .unwrap();
+let compiled = Validator::ALL[0].compile_schema(&schema, None).unwrap();
+let errors = compiled.validate_data(&json::json!(1)).unwrap();
+assert_eq!(errors.len(), 1);
+```
+
+Failures are returned as [`Error`] with context like path, format or URL
+and underlying source error. Warnings and progress are logged using
+[log](https://crates.io/crates/log) crate.
+
+*/
+
+// `pub(self)` marks imports which is shared with submodules
+#![allow(clippy::needless_pub_self)]
+
+mod cache;
+mod error;
+mod parse;
+pub mod utils;
+
+#[cfg(any(
+    feature = "jsonschema",
+    feature = "jsonschema-valid",
+    feature = "valico"
+))]
+mod validate;
+
+#[cfg(feature = "schemastore")]
+mod schemastore;
+
+#[cfg(feature = "http_req")]
+mod http;
+
+pub use url::Url as Uri;
+
+#[cfg(feature = "schemastore")]
+pub use schemastore::{FileMatcher, SchemaInfo, SchemaList, SchemaStore};
+
+pub use cache::Cache;
+pub use error::{BoxError, Error, Result};
+pub use parse::{Format, Location, SourceMap};
+
+#[cfg(any(
+    feature = "jsonschema",
+    feature = "jsonschema-valid",
+    feature = "valico"
+))]
+pub use validate::{
+    fill_defaults, Branches, Comparison, CompiledSchema, FormatMode, Formats, OutputFormat,
+    Reporter, Resolver, Standard, ValidationError, Validator,
+};
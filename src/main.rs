@@ -1,77 +1,164 @@
-mod cache;
-mod command;
-mod parse;
-pub mod utils;
+// `pub(self)` marks imports which is shared with submodules
+#![allow(clippy::needless_pub_self)]
 
-#[cfg(any(
-    feature = "jsonschema",
-    feature = "jsonschema-valid",
-    feature = "valico"
-))]
-mod validate;
+mod command;
 
-#[cfg(feature = "schemastore")]
-mod schemastore;
+#[cfg(feature = "watch")]
+mod watch;
 
-#[cfg(feature = "http_req")]
-mod http;
+#[cfg(any(feature = "toml", feature = "yaml"))]
+mod config;
 
-pub use url::Url as Uri;
+pub use jsonst::{utils, Error, Format, Result, Uri};
 
 #[cfg(feature = "schemastore")]
-pub use schemastore::SchemaStore;
+pub use jsonst::SchemaStore;
 
-pub use cache::Cache;
-pub use command::Args;
-pub use parse::Format;
+pub use jsonst::Cache;
 
 #[cfg(any(
     feature = "jsonschema",
     feature = "jsonschema-valid",
     feature = "valico"
 ))]
-pub use validate::{CompiledSchema, Standard, Validator};
+pub use jsonst::{
+    fill_defaults, Branches, Comparison, CompiledSchema, FormatMode, Formats, OutputFormat,
+    Reporter, Resolver, Standard, Validator,
+};
 
-/// Exit error codes
+pub use command::Args;
+#[cfg(any(feature = "toml", feature = "yaml"))]
+pub use config::Config;
+
+/// Successful outcome of command
+///
+/// Numeric values are process exit codes.
 #[derive(Clone, Copy)]
 #[repr(i32)]
-pub enum Error {
-    Read = -1,
-    Write = -2,
-    Open = -3,
-    Create = -4,
-    Conflict = -5,
-    Query = -6,
-    Parse = -7,
-    Compile = -8,
-    Format = -9,
+pub enum Status {
+    /// Inputs are valid or command succeeded
+    Success = 0,
+    /// Some inputs are invalid, validators disagree or nothing is found
+    Failure = 1,
 }
 
-/// Result alias
-pub type Result<T> = std::result::Result<T, Error>;
+impl Status {
+    /// Get status by number of failures
+    pub fn from_failures(failures: u32) -> Self {
+        if failures > 0 {
+            Self::Failure
+        } else {
+            Self::Success
+        }
+    }
+}
 
 /// Program state
 pub struct State {
     #[cfg(feature = "schemastore")]
     pub schema_store: SchemaStore,
+
+    #[cfg(any(
+        feature = "jsonschema",
+        feature = "jsonschema-valid",
+        feature = "valico"
+    ))]
+    pub resolver: Resolver,
+
+    #[cfg(any(
+        feature = "jsonschema",
+        feature = "jsonschema-valid",
+        feature = "valico"
+    ))]
+    pub formats: Formats,
+
+    /// Discovered configuration or the reason why it is unusable
+    #[cfg(any(feature = "toml", feature = "yaml"))]
+    pub config: std::result::Result<Option<Config>, String>,
 }
 
-#[paw::main]
-fn main(mut args: Args) {
-    std::env::set_var("LOG_LEVEL", &args.log_level);
-    pretty_env_logger::init_custom_env("LOG_LEVEL");
+fn main() {
+    let matches = match <Args as structopt::StructOpt>::clap().get_matches_safe() {
+        Ok(matches) => matches,
+        // help and version is printed to stdout
+        Err(error) if !error.use_stderr() => error.exit(),
+        Err(error) => {
+            eprintln!("{}", error.message);
+            std::process::exit(Error::USAGE);
+        }
+    };
+    let mut args = <Args as structopt::StructOpt>::from_clap(&matches);
+
+    // options from configuration is applied before initializing logging
+    // but only when it isn't given by command-line or environment
+    #[cfg(any(feature = "toml", feature = "yaml"))]
+    let config = Config::discover().and_then(|config| {
+        if let Some(config) = &config {
+            config.apply(&mut args, &matches)?;
+        }
+        Ok(config)
+    });
+
+    pretty_env_logger::formatted_builder()
+        .parse_filters(&args.log_level)
+        .init();
 
     //log::debug!("Args: {:?}", args);
 
+    // invalid configuration fails only commands which uses it
+    #[cfg(any(feature = "toml", feature = "yaml"))]
+    match &config {
+        Ok(Some(config)) => {
+            log::info!("Using configuration from '{}'", config.dir.display())
+        }
+        Ok(None) => (),
+        Err(error) => log::warn!("{}. Configuration is ignored.", error),
+    }
+
     args.fix_cache();
 
     let state = State {
         #[cfg(feature = "schemastore")]
-        schema_store: SchemaStore::new(&args),
+        schema_store: SchemaStore::new(
+            args.catalog_url.clone(),
+            Cache::open(args.cache_dir(), "schemastore"),
+        ),
+
+        #[cfg(any(
+            feature = "jsonschema",
+            feature = "jsonschema-valid",
+            feature = "valico"
+        ))]
+        resolver: {
+            let mut resolver = Resolver::new(Cache::open(args.cache_dir(), "schemas"));
+            for dir in &args.schema_dir {
+                if let Err(error) = resolver.load_dir(dir) {
+                    log::error!("{}", error);
+                    std::process::exit(error.exit_code());
+                }
+            }
+            resolver
+        },
+
+        #[cfg(any(
+            feature = "jsonschema",
+            feature = "jsonschema-valid",
+            feature = "valico"
+        ))]
+        formats: Formats::new(args.format_mode, &args.formats).unwrap_or_else(|error| {
+            log::error!("{}", error);
+            std::process::exit(error.exit_code());
+        }),
+
+        #[cfg(any(feature = "toml", feature = "yaml"))]
+        config,
     };
 
     std::process::exit(match args.run(&state) {
-        Ok(n) => n as i32,
-        Err(e) => e as i32,
+        Ok(status) => status as i32,
+        Err(error) => {
+            log::error!("{}", error);
+            error.exit_code()
+        }
     });
 }
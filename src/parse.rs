@@ -4,8 +4,20 @@ Parsing input data with support for multiple text and binary formats
 
 */
 
+mod source_map;
+
+use crate::{BoxError, Error, Result};
 use std::path::Path;
 
+pub use source_map::{Location, SourceMap};
+
+#[cfg(any(
+    feature = "jsonschema",
+    feature = "jsonschema-valid",
+    feature = "valico"
+))]
+pub use source_map::push_segment;
+
 macro_rules! decl_formats {
     ($(
         $(#[$attr:meta])*
@@ -73,22 +85,28 @@ macro_rules! decl_formats {
             }
 
             /// Unified data parsing
-            pub fn parse_data(&self, topic: &str, path: &Path, data: &[u8]) -> Option<json::Value> {
+            pub fn parse_data(&self, topic: &str, path: &Path, data: &[u8]) -> Result<json::Value> {
                 match self {
                     $(
                         $(#[$attr])*
                         Self::$type => {
                             $name::from_slice(data)
-                                .map_err(|error| {
-                                    log::error!(
-                                        "Unable to parse {} {} from '{}' due to: {}",
-                                        stringify!($name),
-                                        topic,
-                                        path.display(),
-                                        error
-                                    );
-                                })
-                                .ok()
+                                .map_err(|error| self.parse_error(topic, path, error))
+                        }
+                    )*
+                }
+            }
+
+            /// Unified data serializing
+            ///
+            /// Pretty formatting is ignored by formats which doesn't support it.
+            pub fn format_data(&self, topic: &str, data: &json::Value, pretty: bool) -> Result<Vec<u8>> {
+                match self {
+                    $(
+                        $(#[$attr])*
+                        Self::$type => {
+                            $name::to_vec(data, pretty)
+                                .map_err(|error| self.serialize_error(topic, error))
                         }
                     )*
                 }
@@ -97,9 +115,160 @@ macro_rules! decl_formats {
     };
 }
 
+impl Format {
+    /// Determining data format from media type (like `application/schema+json`)
+    pub fn from_media_type(media_type: &str) -> Option<Self> {
+        let essence = media_type.split(';').next()?.trim().to_ascii_lowercase();
+        // structured syntax suffix defines format
+        let name = essence.split('/').nth(1)?.rsplit('+').next()?;
+        match name.strip_prefix("x-").unwrap_or(name) {
+            "ndjson" => Some(Self::Jsonl),
+            name => name.parse().ok(),
+        }
+    }
+
+    /// Check that format is a stream of records
+    ///
+    /// Records of streams should be reported separately even if only one is here.
+    pub fn is_stream(&self) -> bool {
+        *self == Self::Jsonl
+    }
+
+    /// Parse data as sequence of documents with its indexes
+    ///
+    /// Formats without multiple documents support gives single document.
+    /// Empty documents of YAML streams are omitted. Records of streams is parsed
+    /// separately and malformed ones is given as errors with `path#index` paths.
+    pub fn parse_documents(
+        &self,
+        topic: &str,
+        path: &Path,
+        data: &[u8],
+    ) -> Result<Vec<(usize, Result<json::Value>)>> {
+        let documents = match self {
+            Self::Jsonl => {
+                return Ok(jsonl::documents(data)
+                    .into_iter()
+                    .enumerate()
+                    .map(|(index, record)| {
+                        let record = record.map_err(|error| {
+                            let path = format!("{}#{}", path.display(), index);
+                            self.parse_error(topic, Path::new(&path), error)
+                        });
+                        (index, record)
+                    })
+                    .collect());
+            }
+            #[cfg(feature = "yaml")]
+            Self::Yaml => {
+                let documents =
+                    yaml::documents(data).map_err(|error| self.parse_error(topic, path, error))?;
+                if documents.len() > 1 {
+                    return Ok(documents
+                        .into_iter()
+                        .enumerate()
+                        .filter(|(_, document)| !document.is_null())
+                        .map(|(index, document)| (index, Ok(document)))
+                        .collect());
+                }
+                documents
+            }
+            _ => vec![self.parse_data(topic, path, data)?],
+        };
+        Ok(documents.into_iter().map(Ok).enumerate().collect())
+    }
+
+    /// Serialize sequence of documents
+    ///
+    /// Records of streams is written separately (also YAML documents).
+    /// Other formats can hold only single document.
+    pub fn format_documents(
+        &self,
+        topic: &str,
+        documents: &[json::Value],
+        pretty: bool,
+    ) -> Result<Vec<u8>> {
+        match (self, documents) {
+            (Self::Jsonl, _) => {
+                self.format_data(topic, &json::Value::Array(documents.to_vec()), pretty)
+            }
+            #[cfg(feature = "yaml")]
+            (Self::Yaml, _) => {
+                let mut data = Vec::new();
+                for (index, document) in documents.iter().enumerate() {
+                    if index > 0 {
+                        data.extend_from_slice(b"---\n");
+                    }
+                    data.extend(self.format_data(topic, document, pretty)?);
+                }
+                Ok(data)
+            }
+            (_, [document]) => self.format_data(topic, document, pretty),
+            _ => Err(self.serialize_error(
+                topic,
+                format!(
+                    "{} documents cannot be written in single file",
+                    documents.len()
+                ),
+            )),
+        }
+    }
+
+    fn serialize_error(&self, topic: &str, error: impl Into<BoxError>) -> Error {
+        Error::Serialize {
+            topic: topic.into(),
+            format: *self,
+            source: error.into(),
+        }
+    }
+
+    fn parse_error(&self, topic: &str, path: &Path, error: impl Into<BoxError>) -> Error {
+        Error::Parse {
+            topic: topic.into(),
+            format: *self,
+            path: path.into(),
+            source: error.into(),
+            schema: false,
+        }
+    }
+
+    /// Build source maps for each document of text formats
+    pub fn source_maps(&self, data: &[u8]) -> Vec<SourceMap> {
+        let source = if let Ok(source) = std::str::from_utf8(data) {
+            source
+        } else {
+            return Vec::new();
+        };
+        match self {
+            Self::Jsonl => jsonl::source_maps(source),
+            #[cfg(feature = "yaml")]
+            Self::Yaml => yaml::source_maps(source),
+            _ => self.source_map(data).into_iter().collect(),
+        }
+    }
+
+    /// Build source map for text formats
+    pub fn source_map(&self, data: &[u8]) -> Option<SourceMap> {
+        let source = std::str::from_utf8(data).ok()?;
+        match self {
+            Self::Json => json::source_map(source),
+            #[cfg(feature = "json5")]
+            Self::Json5 => json5::source_map(source),
+            #[cfg(feature = "yaml")]
+            Self::Yaml => yaml::source_map(source),
+            #[cfg(feature = "toml")]
+            Self::Toml => toml::source_map(source),
+            #[allow(unreachable_patterns)]
+            _ => None,
+        }
+    }
+}
+
 decl_formats! {
     Json json ["json"];
 
+    Jsonl jsonl ["jsonl", "ndjson"];
+
     #[cfg(feature = "json5")]
     Json5 json5 ["json5"];
 
@@ -224,4 +393,79 @@ mod test {
             )
         );
     }
+
+    #[test]
+    fn format_and_parse() {
+        let data = ::json::json!({ "a": 1, "b": false, "c": [1, true, "a"], "d": { "e": "f" } });
+        let path = Path::new("data");
+        for name in Format::LIST {
+            let format: Format = name.parse().unwrap();
+            for pretty in [false, true] {
+                let output = format.format_data("data", &data, pretty).unwrap();
+                let parsed = format.parse_data("data", path, &output).unwrap();
+                let expected = if format == Format::Jsonl {
+                    ::json::json!([data])
+                } else {
+                    data.clone()
+                };
+                assert_eq!(parsed, expected, "{}", format);
+            }
+        }
+    }
+
+    #[test]
+    fn media_types() {
+        assert_eq!(
+            Format::from_media_type("application/json"),
+            Some(Format::Json)
+        );
+        assert_eq!(
+            Format::from_media_type("application/schema+json; charset=utf-8"),
+            Some(Format::Json)
+        );
+        assert_eq!(
+            Format::from_media_type("application/x-ndjson"),
+            Some(Format::Jsonl)
+        );
+        #[cfg(feature = "yaml")]
+        assert_eq!(Format::from_media_type("text/x-yaml"), Some(Format::Yaml));
+        assert_eq!(Format::from_media_type("text/plain"), None);
+        assert_eq!(Format::from_media_type("json"), None);
+    }
+
+    #[test]
+    fn malformed_records() {
+        let path = Path::new("data.jsonl");
+        let documents = Format::Jsonl
+            .parse_documents("data", path, b"{\"a\": 1}\n{\"b\":\n{\"c\": 3}\n")
+            .unwrap();
+        assert_eq!(documents.len(), 3);
+        assert_eq!(documents[0].1.as_ref().unwrap()["a"], 1);
+        assert_eq!(
+            documents[1].1.as_ref().unwrap_err().to_string(),
+            "Unable to parse jsonl data from 'data.jsonl#1' due to: record at line 2: EOF while parsing a value at line 1 column 5"
+        );
+        assert_eq!(documents[2].1.as_ref().unwrap()["c"], 3);
+    }
+
+    #[test]
+    fn format_documents() {
+        let documents = [::json::json!({ "a": 1 }), ::json::json!({ "b": 2 })];
+        assert_eq!(
+            Format::Jsonl
+                .format_documents("data", &documents, true)
+                .unwrap(),
+            b"{\"a\":1}\n{\"b\":2}\n"
+        );
+        #[cfg(feature = "yaml")]
+        assert_eq!(
+            Format::Yaml
+                .format_documents("data", &documents, false)
+                .unwrap(),
+            b"a: 1\n---\nb: 2\n"
+        );
+        assert!(Format::Json
+            .format_documents("data", &documents, false)
+            .is_err());
+    }
 }
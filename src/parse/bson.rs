@@ -12,3 +12,11 @@ where
     let doc = bson::Document::from_reader(&mut s)?;
     bson::from_document(doc)
 }
+
+/// Serialize data as document (only objects is supported)
+pub fn to_vec(data: &json::Value, _pretty: bool) -> bson::ser::Result<Vec<u8>> {
+    let doc = bson::to_document(data)?;
+    let mut data = Vec::new();
+    doc.to_writer(&mut data)?;
+    Ok(data)
+}
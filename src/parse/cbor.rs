@@ -5,3 +5,7 @@
 */
 
 pub use cbor::from_slice;
+
+pub fn to_vec(data: &json::Value, _pretty: bool) -> cbor::Result<Vec<u8>> {
+    cbor::to_vec(data)
+}
@@ -4,4 +4,257 @@
 
 */
 
+use super::source_map::{push_segment, Builder, SourceMap};
+
 pub use json::{from_slice, Value};
+
+pub fn to_vec(data: &Value, pretty: bool) -> json::Result<Vec<u8>> {
+    if pretty {
+        json::to_vec_pretty(data)
+    } else {
+        json::to_vec(data)
+    }
+}
+
+/// Build source map for JSON text
+///
+/// The scanner is lenient so it also accepts JSON5 extensions like comments,
+/// single-quoted strings, unquoted keys and trailing commas.
+pub fn source_map(source: &str) -> Option<SourceMap> {
+    let mut scanner = Scanner {
+        builder: Builder::new(source),
+        source: source.as_bytes(),
+        pos: 0,
+    };
+    scanner.skip_spaces();
+    scanner.value(String::new())?;
+    Some(scanner.builder.build())
+}
+
+/// Build source maps for each record of JSON Lines text
+pub fn lines_source_maps(source: &str) -> Vec<SourceMap> {
+    let mut scanner = Scanner {
+        builder: Builder::new(source),
+        source: source.as_bytes(),
+        pos: 0,
+    };
+    let mut maps = Vec::new();
+    let mut start = 0;
+    for line in source.split_inclusive('\n') {
+        if !line.trim().is_empty() {
+            scanner.pos = start;
+            scanner.skip_spaces();
+            // records which cannot be scanned have empty source maps
+            let _ = scanner.value(String::new());
+            maps.push(scanner.builder.take());
+        }
+        start += line.len();
+    }
+    maps
+}
+
+struct Scanner<'s> {
+    builder: Builder<'s>,
+    source: &'s [u8],
+    pos: usize,
+}
+
+impl<'s> Scanner<'s> {
+    fn peek(&self) -> Option<u8> {
+        self.source.get(self.pos).copied()
+    }
+
+    fn skip_spaces(&mut self) {
+        while let Some(chr) = self.peek() {
+            if chr.is_ascii_whitespace() {
+                self.pos += 1;
+            } else if self.source[self.pos..].starts_with(b"//") {
+                while !matches!(self.peek(), None | Some(b'\n')) {
+                    self.pos += 1;
+                }
+            } else if self.source[self.pos..].starts_with(b"/*") {
+                self.pos += 2;
+                while self.pos < self.source.len() && !self.source[self.pos..].starts_with(b"*/") {
+                    self.pos += 1;
+                }
+                self.pos = (self.pos + 2).min(self.source.len());
+            } else if self.source[self.pos..].starts_with("\u{feff}".as_bytes()) {
+                self.pos += 3;
+            } else {
+                break;
+            }
+        }
+    }
+
+    fn value(&mut self, pointer: String) -> Option<()> {
+        let start = self.pos;
+        match self.peek()? {
+            b'{' => {
+                self.pos += 1;
+                loop {
+                    self.skip_spaces();
+                    if self.peek()? == b'}' {
+                        break;
+                    }
+                    let key = self.key()?;
+                    self.skip_spaces();
+                    if self.peek()? != b':' {
+                        return None;
+                    }
+                    self.pos += 1;
+                    self.skip_spaces();
+                    self.value(push_segment(&pointer, &key))?;
+                    self.skip_spaces();
+                    if self.peek()? == b',' {
+                        self.pos += 1;
+                    }
+                }
+                self.pos += 1;
+            }
+            b'[' => {
+                self.pos += 1;
+                let mut index = 0usize;
+                loop {
+                    self.skip_spaces();
+                    if self.peek()? == b']' {
+                        break;
+                    }
+                    self.value(push_segment(&pointer, &index.to_string()))?;
+                    index += 1;
+                    self.skip_spaces();
+                    if self.peek()? == b',' {
+                        self.pos += 1;
+                    }
+                }
+                self.pos += 1;
+            }
+            b'"' | b'\'' => {
+                self.string()?;
+            }
+            _ => {
+                while let Some(chr) = self.peek() {
+                    if chr.is_ascii_whitespace() || matches!(chr, b',' | b']' | b'}' | b'/') {
+                        break;
+                    }
+                    self.pos += 1;
+                }
+                if self.pos == start {
+                    return None;
+                }
+            }
+        }
+        self.builder.insert(pointer, start..self.pos);
+        Some(())
+    }
+
+    fn key(&mut self) -> Option<String> {
+        if matches!(self.peek()?, b'"' | b'\'') {
+            self.string()
+        } else {
+            // unquoted identifier
+            let start = self.pos;
+            while let Some(chr) = self.peek() {
+                if chr.is_ascii_whitespace() || chr == b':' {
+                    break;
+                }
+                self.pos += 1;
+            }
+            let source = self.builder.source();
+            Some(source.get(start..self.pos)?.into())
+        }
+    }
+
+    fn string(&mut self) -> Option<String> {
+        let quote = self.peek()?;
+        self.pos += 1;
+        let source = self.builder.source();
+        let mut value = String::new();
+        loop {
+            let chr = source.get(self.pos..)?.chars().next()?;
+            self.pos += chr.len_utf8();
+            match chr {
+                '\\' => {
+                    let chr = source.get(self.pos..)?.chars().next()?;
+                    self.pos += chr.len_utf8();
+                    match chr {
+                        'b' => value.push('\u{8}'),
+                        'f' => value.push('\u{c}'),
+                        'n' => value.push('\n'),
+                        'r' => value.push('\r'),
+                        't' => value.push('\t'),
+                        'u' => {
+                            let mut code = self.hex4()?;
+                            if (0xd800..0xdc00).contains(&code)
+                                && source.get(self.pos..)?.starts_with("\\u")
+                            {
+                                // surrogate pair
+                                self.pos += 2;
+                                let low = self.hex4()?;
+                                code = if (0xdc00..0xe000).contains(&low) {
+                                    0x10000 + ((code - 0xd800) << 10) + (low - 0xdc00)
+                                } else {
+                                    0xfffd
+                                };
+                            }
+                            value.push(char::from_u32(code).unwrap_or('\u{fffd}'));
+                        }
+                        // line continuation
+                        '\n' => (),
+                        chr => value.push(chr),
+                    }
+                }
+                chr if chr as u32 == quote as u32 => break,
+                chr => value.push(chr),
+            }
+        }
+        Some(value)
+    }
+
+    fn hex4(&mut self) -> Option<u32> {
+        let code = self.builder.source().get(self.pos..self.pos + 4)?;
+        self.pos += 4;
+        u32::from_str_radix(code, 16).ok()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn json_locations() {
+        let map =
+            source_map("{\n  \"a\": 1,\n  \"b\": [true, \"x\\\"y\"],\n  \"c/d\": {}\n}").unwrap();
+
+        let location = map.get("/a").unwrap();
+        assert_eq!((location.line, location.column), (2, 8));
+        assert_eq!(location.span, 9..10);
+
+        let location = map.get("/b/1").unwrap();
+        assert_eq!((location.line, location.column), (3, 15));
+        assert_eq!(location.span, 26..32);
+
+        assert_eq!(map.get("/c~1d").unwrap().line, 4);
+        assert_eq!(map.get("").unwrap().span.start, 0);
+        assert_eq!(map.find("/b/1/x").unwrap().line, 3);
+        assert!(map.get("/x").is_none());
+    }
+
+    #[test]
+    fn json_lines_locations() {
+        let maps = lines_source_maps("{\"a\": 1}\n\n  [true, {\"b\": \"x\"}]\n");
+        assert_eq!(maps.len(), 2);
+        assert_eq!(maps[0].get("/a").unwrap().to_string(), "1:7");
+        assert_eq!(maps[1].get("/1/b").unwrap().to_string(), "3:16");
+        assert!(maps[1].get("/a").is_none());
+    }
+
+    #[test]
+    fn json5_locations() {
+        let map = source_map("// comment\n{a: 1, 'b': [1, 2,], /* c */ c: 'x'}").unwrap();
+
+        assert_eq!(map.get("/a").unwrap().column, 5);
+        assert_eq!(map.get("/b/1").unwrap().column, 17);
+        assert_eq!(map.get("/c").unwrap().column, 33);
+    }
+}
@@ -13,3 +13,10 @@ where
     let s = std::str::from_utf8(s).map_err(|error| json5::Error::Message { msg: error.to_string(), location: None })?;
     from_str(s)
 }
+
+pub fn to_vec(data: &json::Value, _pretty: bool) -> json5::Result<Vec<u8>> {
+    json5::to_string(data).map(String::into_bytes)
+}
+
+/// JSON scanner is lenient enough to handle JSON5
+pub use super::json::source_map;
@@ -0,0 +1,87 @@
+/*!
+
+[JSON Lines](https://jsonlines.org/) format (text stream)
+
+*/
+
+use json::Value;
+use std::fmt::{Display, Formatter, Result as FmtResult};
+
+/// Record parsing error
+#[derive(Debug)]
+pub struct Error {
+    /// Line number (starting from 1)
+    line: usize,
+    error: json::Error,
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        write!(f, "record at line {}: {}", self.line, self.error)
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+/// Parse all records as array
+pub fn from_slice(data: &[u8]) -> Result<Value, Error> {
+    documents(data)
+        .into_iter()
+        .collect::<Result<_, _>>()
+        .map(Value::Array)
+}
+
+/// Write each record of array as separate line
+pub fn to_vec(data: &Value, _pretty: bool) -> Result<Vec<u8>, json::Error> {
+    let records = match data {
+        Value::Array(records) => records.as_slice(),
+        record => std::slice::from_ref(record),
+    };
+    let mut data = Vec::new();
+    for record in records {
+        json::to_writer(&mut data, record)?;
+        data.push(b'\n');
+    }
+    Ok(data)
+}
+
+/// Parse each non-empty line as separate record
+///
+/// Records is parsed independently so malformed one doesn't affect others.
+pub fn documents(data: &[u8]) -> Vec<Result<Value, Error>> {
+    data.split(|byte| *byte == b'\n')
+        .enumerate()
+        .filter(|(_, line)| !line.iter().all(u8::is_ascii_whitespace))
+        .map(|(index, line)| {
+            json::from_slice(line).map_err(|error| Error {
+                line: index + 1,
+                error,
+            })
+        })
+        .collect()
+}
+
+pub use super::json::lines_source_maps as source_maps;
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse_records() {
+        let records = from_slice(b"{\"a\": 1}\n\n[2]\r\n").unwrap();
+        assert_eq!(records, json::json!([{ "a": 1 }, [2]]));
+
+        let records = documents(b"1\n\n{\n3\n");
+        assert_eq!(records.len(), 3);
+        assert_eq!(records[0].as_ref().unwrap(), &json::json!(1));
+        let error = records[1].as_ref().unwrap_err();
+        assert!(error.to_string().starts_with("record at line 3: "));
+        assert_eq!(records[2].as_ref().unwrap(), &json::json!(3));
+        assert!(from_slice(b"1\n{\n3\n").is_err());
+    }
+}
@@ -9,3 +9,7 @@ pub fn from_slice<'de, T: serde::Deserialize<'de>>(
 ) -> pickle::Result<T> {
     pickle::from_slice(data, pickle::de::DeOptions::default())
 }
+
+pub fn to_vec(data: &json::Value, _pretty: bool) -> pickle::Result<Vec<u8>> {
+    pickle::to_vec(data, pickle::ser::SerOptions::default())
+}
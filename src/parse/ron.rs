@@ -5,3 +5,12 @@
 */
 
 pub use ron::de::from_bytes as from_slice;
+
+pub fn to_vec(data: &json::Value, pretty: bool) -> ron::Result<Vec<u8>> {
+    if pretty {
+        ron::ser::to_string_pretty(data, Default::default())
+    } else {
+        ron::ser::to_string(data)
+    }
+    .map(String::into_bytes)
+}
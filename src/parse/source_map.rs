@@ -0,0 +1,125 @@
+/*!
+
+Mapping values in parsed data to locations in source text
+
+*/
+
+use std::{
+    collections::HashMap,
+    fmt::{Display, Formatter, Result as FmtResult},
+    ops::Range,
+};
+
+/// Location of value in source text
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Location {
+    /// Line number (starting from 1)
+    pub line: usize,
+    /// Column number in characters (starting from 1)
+    pub column: usize,
+    /// Byte span of value
+    pub span: Range<usize>,
+}
+
+impl Display for Location {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        self.line.fmt(f)?;
+        ':'.fmt(f)?;
+        self.column.fmt(f)
+    }
+}
+
+/// Side table which maps JSON pointers to source locations
+#[derive(Clone, Debug, Default)]
+pub struct SourceMap {
+    locations: HashMap<String, Location>,
+}
+
+impl SourceMap {
+    /// Get location of value by JSON pointer
+    pub fn get(&self, pointer: &str) -> Option<&Location> {
+        self.locations.get(pointer)
+    }
+
+    /// Get location of value or its nearest ancestor by JSON pointer
+    pub fn find(&self, pointer: &str) -> Option<&Location> {
+        let mut pointer = pointer;
+        loop {
+            if let Some(location) = self.get(pointer) {
+                return Some(location);
+            }
+            pointer = &pointer[..pointer.rfind('/')?];
+        }
+    }
+}
+
+/// Source map builder
+pub struct Builder<'s> {
+    source: &'s str,
+    line_starts: Vec<usize>,
+    locations: HashMap<String, Location>,
+}
+
+impl<'s> Builder<'s> {
+    pub fn new(source: &'s str) -> Self {
+        let line_starts = core::iter::once(0)
+            .chain(source.match_indices('\n').map(|(index, _)| index + 1))
+            .collect();
+        Self {
+            source,
+            line_starts,
+            locations: HashMap::new(),
+        }
+    }
+
+    /// Source text
+    pub fn source(&self) -> &'s str {
+        self.source
+    }
+
+    /// Add value location by byte span (trailing whitespaces will be trimmed)
+    pub fn insert(&mut self, pointer: String, span: Range<usize>) {
+        let span = span.start..span.start + self.source[span.clone()].trim_end().len();
+        let line = match self.line_starts.binary_search(&span.start) {
+            Ok(line) => line,
+            Err(line) => line - 1,
+        };
+        let line_start = self.line_starts[line];
+        let column = self.source[line_start..span.start].chars().count() + 1;
+        self.locations.insert(
+            pointer,
+            Location {
+                line: line + 1,
+                column,
+                span,
+            },
+        );
+    }
+
+    /// Take source map of values which is added so far
+    pub fn take(&mut self) -> SourceMap {
+        SourceMap {
+            locations: std::mem::take(&mut self.locations),
+        }
+    }
+
+    pub fn build(self) -> SourceMap {
+        SourceMap {
+            locations: self.locations,
+        }
+    }
+}
+
+/// Append segment to JSON pointer
+pub fn push_segment(pointer: &str, segment: &str) -> String {
+    let mut pointer = String::from(pointer);
+    pointer.push('/');
+    for ch in segment.chars() {
+        match ch {
+            '~' => pointer.push_str("~0"),
+            '/' => pointer.push_str("~1"),
+            _ => pointer.push(ch),
+        }
+    }
+    pointer
+}
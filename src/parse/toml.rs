@@ -4,4 +4,118 @@
 
 */
 
+use super::source_map::{push_segment, Builder, SourceMap};
+use serde::de::{Deserialize, Deserializer, MapAccess, SeqAccess, Visitor};
+use std::fmt::{Formatter, Result as FmtResult};
+use toml::Spanned;
+
 pub use toml::from_slice;
+
+/// Serialize data through TOML value which puts tables after plain values
+pub fn to_vec(data: &json::Value, pretty: bool) -> Result<Vec<u8>, toml::ser::Error> {
+    let data = toml::Value::try_from(data)?;
+    if pretty {
+        toml::to_string_pretty(&data)
+    } else {
+        toml::to_string(&data)
+    }
+    .map(String::into_bytes)
+}
+
+/// Build source map for TOML text
+pub fn source_map(source: &str) -> Option<SourceMap> {
+    let root: Node = toml::from_str(source).ok()?;
+    let mut builder = Builder::new(source);
+    builder.insert(String::new(), 0..source.len());
+    root.collect(&mut builder, "");
+    Some(builder.build())
+}
+
+/// Value which keeps spans of nested values
+struct Node(Vec<(String, Spanned<Node>)>);
+
+impl Node {
+    fn collect(&self, builder: &mut Builder, pointer: &str) {
+        for (key, node) in &self.0 {
+            let pointer = push_segment(pointer, key);
+            let span = node.start()..node.end();
+            node.get_ref().collect(builder, &pointer);
+            builder.insert(pointer, span);
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Node {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(NodeVisitor)
+    }
+}
+
+struct NodeVisitor;
+
+macro_rules! visit_scalars {
+    ($($name:ident: $type:ty;)*) => {
+        $(
+            fn $name<E>(self, _: $type) -> Result<Node, E> {
+                Ok(Node(Vec::new()))
+            }
+        )*
+    };
+}
+
+impl<'de> Visitor<'de> for NodeVisitor {
+    type Value = Node;
+
+    fn expecting(&self, f: &mut Formatter) -> FmtResult {
+        f.write_str("any TOML value")
+    }
+
+    visit_scalars! {
+        visit_bool: bool;
+        visit_i64: i64;
+        visit_u64: u64;
+        visit_f64: f64;
+        visit_str: &str;
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Node, A::Error> {
+        let mut nodes = Vec::new();
+        while let Some(node) = seq.next_element()? {
+            nodes.push((nodes.len().to_string(), node));
+        }
+        Ok(Node(nodes))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Node, A::Error> {
+        let mut nodes = Vec::new();
+        while let Some(entry) = map.next_entry()? {
+            nodes.push(entry);
+        }
+        Ok(Node(nodes))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn toml_locations() {
+        let source = "a = 1\n\n[topic]\nb = 'x'\nc = [1, true]\n\n[[list]]\nd = 2\n";
+        let map = source_map(source).unwrap();
+
+        let location = map.get("/a").unwrap();
+        assert_eq!((location.line, location.column), (1, 5));
+        assert_eq!(&source[location.span.clone()], "1");
+
+        let location = map.get("/topic/b").unwrap();
+        assert_eq!((location.line, location.column), (4, 5));
+        assert_eq!(&source[location.span.clone()], "'x'");
+
+        let location = map.get("/topic/c/1").unwrap();
+        assert_eq!((location.line, location.column), (5, 9));
+
+        let location = map.get("/list/0/d").unwrap();
+        assert_eq!((location.line, location.column), (8, 5));
+    }
+}
@@ -2,6 +2,509 @@
 
 [YAML](https://yaml.org/) format (text)
 
+Documents are loaded from parser events so values and its source locations
+comes from the same parse.
+
 */
 
-pub use yaml::from_slice;
+use super::source_map::{push_segment, Builder, SourceMap};
+use json::{Map, Value};
+use std::collections::HashMap;
+use yaml_rust2::{
+    parser::{Event, MarkedEventReceiver, Parser, Tag},
+    scanner::{Marker, TScalarStyle},
+};
+
+/// Maximum number of nodes expanded from aliases in document
+///
+/// It prevents exponential growth of documents by nested aliases ("billion laughs").
+const EXPANSION_LIMIT: usize = 1_000_000;
+
+pub fn to_vec(data: &Value, _pretty: bool) -> Result<Vec<u8>, yaml::Error> {
+    yaml::to_string(data).map(String::into_bytes)
+}
+
+/// Parse single YAML document (empty stream gives null)
+pub fn from_slice(data: &[u8]) -> Result<Value, Error> {
+    let mut documents = load(data, false)?.0;
+    match documents.len() {
+        0 => Ok(Value::Null),
+        1 => Ok(documents.remove(0)),
+        _ => Err(Error::new(
+            "more than one document in stream where single is expected",
+            None,
+        )),
+    }
+}
+
+/// Parse each document of YAML stream
+pub fn documents(data: &[u8]) -> Result<Vec<Value>, Error> {
+    load(data, false).map(|(documents, _)| documents)
+}
+
+/// Build source map for YAML text (only first document is mapped)
+pub fn source_map(source: &str) -> Option<SourceMap> {
+    source_maps(source).into_iter().next()
+}
+
+/// Build source maps for each document of YAML stream
+pub fn source_maps(source: &str) -> Vec<SourceMap> {
+    load(source.as_bytes(), true)
+        .map(|(_, maps)| maps)
+        .unwrap_or_default()
+}
+
+/// Load documents with its source maps when requested
+fn load(data: &[u8], locate: bool) -> Result<(Vec<Value>, Vec<SourceMap>), Error> {
+    let source = std::str::from_utf8(data).map_err(|error| Error::new(error, None))?;
+    let mut loader = Loader {
+        // yaml-rust2 reports character offsets so we need convert it to bytes
+        offsets: source
+            .char_indices()
+            .map(|(offset, _)| offset)
+            .chain(core::iter::once(source.len()))
+            .collect(),
+        builder: locate.then(|| Builder::new(source)),
+        source,
+        stack: Vec::new(),
+        scalar: None,
+        anchors: HashMap::new(),
+        nodes: 0,
+        expanded: 0,
+        documents: Vec::new(),
+        maps: Vec::new(),
+        error: None,
+    };
+    Parser::new_from_str(source)
+        .load(&mut loader, true)
+        .map_err(|error| {
+            let mark = error.marker();
+            Error::new(error.info(), Some((mark.line(), mark.col() + 1)))
+        })?;
+    match loader.error {
+        Some(error) => Err(error),
+        None => Ok((loader.documents, loader.maps)),
+    }
+}
+
+/// YAML parsing error
+#[derive(Debug)]
+pub struct Error {
+    message: String,
+    /// Line and column where error occurred
+    location: Option<(usize, usize)>,
+}
+
+impl Error {
+    fn new(message: impl ToString, location: Option<(usize, usize)>) -> Self {
+        Self {
+            message: message.to_string(),
+            location,
+        }
+    }
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        self.message.fmt(f)?;
+        if let Some((line, column)) = self.location {
+            write!(f, " at line {} column {}", line, column)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for Error {}
+
+enum Frame {
+    Mapping {
+        pointer: String,
+        start: usize,
+        anchor: usize,
+        /// Number of document nodes before this one
+        nodes: usize,
+        object: Map<String, Value>,
+        key: Option<String>,
+        /// Block mapping starts with first key
+        block: bool,
+    },
+    Sequence {
+        pointer: String,
+        start: usize,
+        anchor: usize,
+        /// Number of document nodes before this one
+        nodes: usize,
+        array: Vec<Value>,
+    },
+}
+
+struct Loader<'s> {
+    source: &'s str,
+    offsets: Vec<usize>,
+    builder: Option<Builder<'s>>,
+    stack: Vec<Frame>,
+    /// Scalar which end is unknown until next event
+    scalar: Option<(String, usize)>,
+    /// Anchored values of current document with its numbers of nodes
+    anchors: HashMap<usize, (Value, usize)>,
+    /// Number of nodes of current document
+    nodes: usize,
+    /// Number of nodes of current document expanded from aliases
+    expanded: usize,
+    /// Completed documents
+    documents: Vec<Value>,
+    /// Source maps of completed documents
+    maps: Vec<SourceMap>,
+    /// Error which stops loading
+    error: Option<Error>,
+}
+
+impl<'s> Loader<'s> {
+    fn offset(&self, mark: &Marker) -> usize {
+        self.offsets[mark.index().min(self.offsets.len() - 1)]
+    }
+
+    fn locate(&mut self, pointer: String, span: std::ops::Range<usize>) {
+        if let Some(builder) = &mut self.builder {
+            builder.insert(pointer, span);
+        }
+    }
+
+    /// Check that next node is a mapping key
+    fn is_key(&self) -> bool {
+        matches!(self.stack.last(), Some(Frame::Mapping { key: None, .. }))
+    }
+
+    /// Get pointer of node which starts here
+    fn pointer(&self) -> String {
+        match self.stack.last() {
+            None => String::new(),
+            Some(Frame::Mapping { pointer, key, .. }) => {
+                push_segment(pointer, key.as_deref().unwrap_or_default())
+            }
+            Some(Frame::Sequence { pointer, array, .. }) => {
+                push_segment(pointer, &array.len().to_string())
+            }
+        }
+    }
+
+    fn set_key(&mut self, new_key: String, offset: usize) {
+        if let Some(Frame::Mapping {
+            key, start, block, ..
+        }) = self.stack.last_mut()
+        {
+            *key = Some(new_key);
+            if *block {
+                // block mapping start mark is inaccurate
+                *start = offset;
+                *block = false;
+            }
+        }
+    }
+
+    /// Put completed node into its parent
+    ///
+    /// Nodes counts number of nodes of value since its start.
+    fn insert(&mut self, value: Value, anchor: usize, nodes: usize) {
+        if anchor > 0 {
+            self.anchors.insert(anchor, (value.clone(), nodes));
+        }
+        match self.stack.last_mut() {
+            None => self.documents.push(value),
+            Some(Frame::Mapping { object, key, .. }) => {
+                if let Some(key) = key.take() {
+                    object.insert(key, value);
+                }
+            }
+            Some(Frame::Sequence { array, .. }) => array.push(value),
+        }
+    }
+
+    fn fail(&mut self, message: &str, mark: &Marker) {
+        if self.error.is_none() {
+            self.error = Some(Error::new(message, Some((mark.line(), mark.col() + 1))));
+        }
+    }
+}
+
+impl MarkedEventReceiver for Loader<'_> {
+    fn on_event(&mut self, event: Event, mark: Marker) {
+        if self.error.is_some() {
+            return;
+        }
+        let offset = self.offset(&mark);
+
+        if let Some((pointer, start)) = self.scalar.take() {
+            self.locate(pointer, start..offset.max(start));
+        }
+
+        match event {
+            Event::Scalar(value, style, anchor, tag) => {
+                if self.is_key() {
+                    self.set_key(value, offset);
+                    return;
+                }
+                let pointer = self.pointer();
+                if self.builder.is_some() {
+                    // end of plain scalar is known
+                    if style == TScalarStyle::Plain
+                        && self.source[offset..].starts_with(value.as_str())
+                    {
+                        self.locate(pointer, offset..offset + value.len());
+                    } else {
+                        self.scalar = Some((pointer, offset));
+                    }
+                }
+                self.nodes += 1;
+                self.insert(resolve_scalar(value, style, tag.as_ref()), anchor, 1);
+            }
+            Event::Alias(anchor) => {
+                let (value, nodes) = match self.anchors.get(&anchor) {
+                    Some(anchored) => anchored.clone(),
+                    None => return self.fail("unknown anchor", &mark),
+                };
+                self.expanded += nodes;
+                if self.expanded > EXPANSION_LIMIT {
+                    return self.fail("repetition limit exceeded", &mark);
+                }
+                self.nodes += nodes;
+                if self.is_key() {
+                    match value {
+                        Value::String(key) => self.set_key(key, offset),
+                        _ => self.fail("mapping key should be a scalar", &mark),
+                    }
+                    return;
+                }
+                if self.builder.is_some() {
+                    self.scalar = Some((self.pointer(), offset));
+                }
+                self.insert(value, 0, nodes);
+            }
+            Event::MappingStart(anchor, _) | Event::SequenceStart(anchor, _) => {
+                if self.is_key() {
+                    return self.fail("mapping key should be a scalar", &mark);
+                }
+                let pointer = self.pointer();
+                let nodes = self.nodes;
+                self.nodes += 1;
+                self.stack
+                    .push(if matches!(event, Event::MappingStart(..)) {
+                        Frame::Mapping {
+                            pointer,
+                            start: offset,
+                            anchor,
+                            nodes,
+                            object: Map::new(),
+                            key: None,
+                            block: self.source.as_bytes().get(offset) != Some(&b'{'),
+                        }
+                    } else {
+                        Frame::Sequence {
+                            pointer,
+                            start: offset,
+                            anchor,
+                            nodes,
+                            array: Vec::new(),
+                        }
+                    });
+            }
+            Event::MappingEnd | Event::SequenceEnd => {
+                // flow collections ends after closing bracket
+                let end = match self.source.as_bytes().get(offset) {
+                    Some(b'}' | b']') => offset + 1,
+                    _ => offset,
+                };
+                let (pointer, start, anchor, nodes, value) = match self.stack.pop() {
+                    Some(Frame::Mapping {
+                        pointer,
+                        start,
+                        anchor,
+                        nodes,
+                        object,
+                        ..
+                    }) => (pointer, start, anchor, nodes, Value::Object(object)),
+                    Some(Frame::Sequence {
+                        pointer,
+                        start,
+                        anchor,
+                        nodes,
+                        array,
+                    }) => (pointer, start, anchor, nodes, Value::Array(array)),
+                    None => return,
+                };
+                self.locate(pointer, start..end.max(start));
+                self.insert(value, anchor, self.nodes - nodes);
+            }
+            Event::DocumentEnd => {
+                self.stack.clear();
+                self.anchors.clear();
+                self.nodes = 0;
+                self.expanded = 0;
+                if let Some(builder) = &mut self.builder {
+                    self.maps.push(builder.take());
+                }
+            }
+            _ => (),
+        }
+    }
+}
+
+/// Resolve scalar value using YAML 1.2 core schema
+///
+/// Quoted and block scalars and ones tagged by `!!str` is strings.
+fn resolve_scalar(value: String, style: TScalarStyle, tag: Option<&Tag>) -> Value {
+    let tag = tag
+        .filter(|tag| tag.handle == "tag:yaml.org,2002:" || tag.handle == "!!")
+        .map(|tag| tag.suffix.as_str());
+    if style != TScalarStyle::Plain || tag == Some("str") {
+        return Value::String(value);
+    }
+    match value.as_str() {
+        "" | "~" | "null" | "Null" | "NULL" => return Value::Null,
+        "true" | "True" | "TRUE" => return Value::Bool(true),
+        "false" | "False" | "FALSE" => return Value::Bool(false),
+        // not representable in JSON
+        ".inf" | ".Inf" | ".INF" | "+.inf" | "+.Inf" | "+.INF" | "-.inf" | "-.Inf" | "-.INF"
+        | ".nan" | ".NaN" | ".NAN" => return Value::Null,
+        _ => (),
+    }
+    if let Some(number) = resolve_integer(&value) {
+        return number;
+    }
+    let is_float = value.contains(|chr: char| chr.is_ascii_digit())
+        && value
+            .chars()
+            .all(|chr| chr.is_ascii_digit() || "+-.eE".contains(chr));
+    if let Some(number) = is_float
+        .then(|| value.parse::<f64>().ok())
+        .flatten()
+        .and_then(json::Number::from_f64)
+    {
+        return Value::Number(number);
+    }
+    Value::String(value)
+}
+
+fn resolve_integer(value: &str) -> Option<Value> {
+    let (negative, digits) = match value.as_bytes().first()? {
+        b'-' => (true, &value[1..]),
+        b'+' => (false, &value[1..]),
+        _ => (false, value),
+    };
+    let (radix, digits) = if let Some(digits) = digits.strip_prefix("0x") {
+        (16, digits)
+    } else if let Some(digits) = digits.strip_prefix("0o") {
+        (8, digits)
+    } else {
+        (10, digits)
+    };
+    if digits.is_empty() || !digits.chars().all(|chr| chr.is_digit(radix)) {
+        return None;
+    }
+    let number = u64::from_str_radix(digits, radix).ok()?;
+    Some(if negative {
+        Value::from(0i64.checked_sub_unsigned(number)?)
+    } else {
+        Value::from(number)
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn yaml_values() {
+        let data = from_slice(
+            b"a: 1\nb: [-0x1f, 0o17, 1.5e3, .inf, ~, True, 'yes', no]\nc: !!str 12\n\
+              d: &x {e: null}\nf: *x\ng: |\n  text\n\"h\": -18446744073709551615\n",
+        )
+        .unwrap();
+        assert_eq!(
+            data,
+            json::json!({
+                "a": 1,
+                "b": [-31, 15, 1500.0, null, null, true, "yes", "no"],
+                "c": "12",
+                "d": { "e": null },
+                "f": { "e": null },
+                "g": "text\n",
+                "h": -18446744073709551615.0
+            })
+        );
+        assert_eq!(from_slice(b"").unwrap(), Value::Null);
+        assert!(from_slice(b"a: 1\n---\nb: 2\n").is_err());
+        assert!(from_slice(b"[a, b]: 1\n").is_err());
+        assert_eq!(
+            from_slice(b"a: [1\n").unwrap_err().to_string(),
+            "while parsing a flow sequence, expected ',' or ']' at line 2 column 1"
+        );
+    }
+
+    #[test]
+    fn alias_bomb() {
+        let mut data = String::from("a: &a [x, x, x, x, x, x, x, x, x, x]\n");
+        for (name, prev) in "bcdefgh".chars().zip("abcdefg".chars()) {
+            let items = vec![format!("*{}", prev); 10].join(", ");
+            data.push_str(&format!("{0}: &{0} [{1}]\n", name, items));
+        }
+        let error = from_slice(data.as_bytes()).unwrap_err();
+        assert!(
+            error.to_string().starts_with("repetition limit exceeded"),
+            "{}",
+            error
+        );
+        // limit is counted per document
+        assert_eq!(
+            documents(b"a: &a [1]\nb: *a\n---\nc: &c 2\nd: *c\n")
+                .unwrap()
+                .len(),
+            2
+        );
+    }
+
+    #[test]
+    fn yaml_locations() {
+        let source = "a: 1\nb:\n  - x\n  - {c: 'y', d: [1, 2]}\né: z # comment\nf:\n  g: 1\n";
+        let map = source_map(source).unwrap();
+
+        let location = map.get("/a").unwrap();
+        assert_eq!((location.line, location.column), (1, 4));
+        assert_eq!(&source[location.span.clone()], "1");
+
+        let location = map.get("/b/0").unwrap();
+        assert_eq!((location.line, location.column), (3, 5));
+        assert_eq!(&source[location.span.clone()], "x");
+
+        let location = map.get("/b/1").unwrap();
+        assert_eq!((location.line, location.column), (4, 5));
+        assert_eq!(&source[location.span.clone()], "{c: 'y', d: [1, 2]}");
+
+        let location = map.get("/b/1/d/1").unwrap();
+        assert_eq!((location.line, location.column), (4, 21));
+
+        let location = map.get("/é").unwrap();
+        assert_eq!((location.line, location.column), (5, 4));
+
+        let location = map.get("/f").unwrap();
+        assert_eq!((location.line, location.column), (7, 3));
+        assert_eq!(&source[location.span.clone()], "g: 1");
+
+        let location = map.get("").unwrap();
+        assert_eq!((location.line, location.column), (1, 1));
+    }
+
+    #[test]
+    fn yaml_stream_locations() {
+        let source = "a: 1\n---\n---\nb:\n  - x\n";
+        let maps = source_maps(source);
+        assert_eq!(maps.len(), 3);
+        assert_eq!(maps[0].get("/a").unwrap().to_string(), "1:4");
+        assert!(maps[1].get("/a").is_none());
+        assert_eq!(maps[2].get("/b/0").unwrap().to_string(), "5:5");
+
+        let documents = documents(source.as_bytes()).unwrap();
+        assert_eq!(documents.len(), 3);
+        assert!(documents[1].is_null());
+    }
+}
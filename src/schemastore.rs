@@ -6,8 +6,10 @@ Searching and retrieving JSON Schemas from [schemastore.org](https://schemastore
 
 mod data;
 
-use crate::{http::get_cached, Args, Cache, Uri};
-use data::{SchemaInfo, SchemaList};
+use crate::{http::get_cached, Cache, Error, Result, Uri};
+pub use data::{SchemaInfo, SchemaList};
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+use std::path::Path;
 
 /// SchemaStore handle
 pub struct SchemaStore {
@@ -17,15 +19,12 @@ pub struct SchemaStore {
 
 impl SchemaStore {
     /// Create new handle
-    pub fn new(args: &Args) -> Self {
-        let catalog_url = args.catalog_url.clone();
-        let cache = Cache::open(args, "schemastore");
-
+    pub fn new(catalog_url: Uri, cache: Cache) -> Self {
         Self { catalog_url, cache }
     }
 
     /// Get list of schemas
-    pub fn list(&self) -> Option<SchemaList> {
+    pub fn list(&self) -> Result<SchemaList> {
         let url = self.catalog_url.to_string();
         get_cached(&self.cache, url)
     }
@@ -36,7 +35,7 @@ impl SchemaStore {
         patterns: I,
         in_names: bool,
         in_descriptions: bool,
-    ) -> Option<Vec<SchemaInfo>>
+    ) -> Result<Vec<SchemaInfo>>
     where
         S: AsRef<str>,
         I: IntoIterator<Item = S>,
@@ -72,58 +71,212 @@ impl SchemaStore {
         patterns: I,
         in_names: bool,
         in_descriptions: bool,
-    ) -> Option<SchemaInfo>
+    ) -> Result<SchemaInfo>
     where
         S: AsRef<str>,
         I: IntoIterator<Item = S>,
     {
-        let schemas = self.find(patterns, in_names, in_descriptions)?;
-        let count = schemas.len();
-        if count == 1 {
-            Some(schemas.into_iter().next().unwrap())
-        } else {
-            if count == 0 {
-                log::error!("No schemas found.");
-            } else {
-                log::error!("Multiple schemas found.");
-            }
-            None
+        let patterns = patterns
+            .into_iter()
+            .map(|pattern| pattern.as_ref().to_string())
+            .collect::<Vec<_>>();
+        let mut schemas = self.find(&patterns, in_names, in_descriptions)?;
+        match schemas.len() {
+            1 => Ok(schemas.remove(0)),
+            0 => Err(Error::NotFound(patterns.join(" "))),
+            count => Err(Error::Usage(format!(
+                "Multiple schemas ({}) found by '{}'",
+                count,
+                patterns.join(" ")
+            ))),
         }
     }
 
     /// Get schema by url
-    pub fn get_by_url(&self, url: impl AsRef<Uri>) -> Option<json::Value> {
-        let url = url.as_ref().to_string();
+    pub fn get_by_url(&self, url: &Uri) -> Result<json::Value> {
+        let url = url.to_string();
         get_cached(&self.cache, url)
     }
 
+    /// Get matcher which selects schemas for files using `fileMatch` patterns
+    pub fn file_matcher(&self) -> Result<FileMatcher> {
+        self.list().and_then(FileMatcher::new)
+    }
+
     /// Search schemas using regex-like patterns
     pub fn get_one<I, S>(
         &self,
         patterns: I,
         in_names: bool,
         in_descriptions: bool,
-    ) -> Option<(SchemaInfo, json::Value)>
+    ) -> Result<(SchemaInfo, json::Value)>
     where
         S: AsRef<str>,
         I: IntoIterator<Item = S>,
     {
         let schema = self.find_one(patterns, in_names, in_descriptions)?;
         let content = self.get_by_url(&schema.url)?;
-        Some((schema, content))
+        Ok((schema, content))
     }
 }
 
-fn build_patterns<I, S>(patterns: I) -> Option<regex::RegexSet>
+/// Matcher of file paths to schemas using `fileMatch` patterns
+pub struct FileMatcher {
+    schemas: Vec<SchemaInfo>,
+    globs: GlobSet,
+    /// Schema index and pattern length for each glob
+    targets: Vec<(usize, usize)>,
+    excludes: GlobSet,
+    /// Schema index for each excluding glob
+    excluded: Vec<usize>,
+}
+
+impl FileMatcher {
+    fn new(list: SchemaList) -> Result<Self> {
+        let mut globs = GlobSetBuilder::new();
+        let mut targets = Vec::new();
+        let mut excludes = GlobSetBuilder::new();
+        let mut excluded = Vec::new();
+
+        for (index, schema) in list.schemas.iter().enumerate() {
+            for pattern in schema.file_match.iter().flatten() {
+                let (pattern, exclude) = if let Some(pattern) = pattern.strip_prefix('!') {
+                    (pattern, true)
+                } else {
+                    (pattern.as_str(), false)
+                };
+                // patterns is matched to any path suffix
+                let glob = if pattern.starts_with('/') || pattern.starts_with("**/") {
+                    pattern.to_string()
+                } else {
+                    format!("**/{}", pattern)
+                };
+                let glob = match GlobBuilder::new(&glob).literal_separator(true).build() {
+                    Ok(glob) => glob,
+                    Err(error) => {
+                        log::debug!(
+                            "Skip invalid pattern '{}' of '{}': {}",
+                            pattern,
+                            schema.name,
+                            error
+                        );
+                        continue;
+                    }
+                };
+                if exclude {
+                    excludes.add(glob);
+                    excluded.push(index);
+                } else {
+                    globs.add(glob);
+                    targets.push((index, pattern.len()));
+                }
+            }
+        }
+
+        Ok(Self {
+            schemas: list.schemas,
+            globs: globs.build().map_err(pattern_error)?,
+            targets,
+            excludes: excludes.build().map_err(pattern_error)?,
+            excluded,
+        })
+    }
+
+    /// Find schema for file
+    ///
+    /// When several schemas matches the one with longest pattern is taken
+    /// as the most specific.
+    pub fn find(&self, path: &Path) -> Option<&SchemaInfo> {
+        let path = path.canonicalize().unwrap_or_else(|_| path.into());
+        let excluded = self
+            .excludes
+            .matches(&path)
+            .into_iter()
+            .map(|index| self.excluded[index])
+            .collect::<Vec<_>>();
+        self.globs
+            .matches(&path)
+            .into_iter()
+            .map(|index| self.targets[index])
+            .filter(|(schema, _)| !excluded.contains(schema))
+            .max_by_key(|(_, length)| *length)
+            .map(|(schema, _)| &self.schemas[schema])
+    }
+}
+
+fn build_patterns<I, S>(patterns: I) -> Result<regex::RegexSet>
 where
     S: AsRef<str>,
     I: IntoIterator<Item = S>,
 {
-    regex::RegexSetBuilder::new(patterns)
+    let patterns = patterns
+        .into_iter()
+        .map(|pattern| pattern.as_ref().to_string())
+        .collect::<Vec<_>>();
+    regex::RegexSetBuilder::new(&patterns)
         .case_insensitive(true)
         .build()
-        .map_err(|error| {
-            log::error!("Invalid pattern: {}", error);
+        .map_err(|error| Error::Pattern {
+            pattern: patterns.join(" "),
+            source: error.into(),
         })
-        .ok()
+}
+
+fn pattern_error(error: globset::Error) -> Error {
+    Error::Pattern {
+        pattern: error.glob().unwrap_or_default().into(),
+        source: error.into(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn match_files() {
+        let list: SchemaList = json::from_value(json::json!({
+            "$schema": "https://json.schemastore.org/schema-catalog",
+            "version": 1.0,
+            "schemas": [
+                {
+                    "name": "package.json",
+                    "description": "NPM configuration file",
+                    "url": "https://json.schemastore.org/package.json",
+                    "fileMatch": ["package.json"]
+                },
+                {
+                    "name": "GitHub Workflow",
+                    "description": "YAML GitHub Workflow",
+                    "url": "https://json.schemastore.org/github-workflow.json",
+                    "fileMatch": ["**/.github/workflows/*.yml", "**/.github/workflows/*.yaml"]
+                },
+                {
+                    "name": "Any YAML",
+                    "description": "Test",
+                    "url": "https://example.com/any.json",
+                    "fileMatch": ["*.yml", "!skip.yml"]
+                }
+            ]
+        }))
+        .unwrap();
+        let matcher = FileMatcher::new(list).unwrap();
+        let find = |path: &str| {
+            matcher
+                .find(Path::new(path))
+                .map(|schema| schema.name.as_str())
+        };
+
+        assert_eq!(find("/project/package.json"), Some("package.json"));
+        assert_eq!(find("/project/package.json5"), None);
+        assert_eq!(
+            find("/project/.github/workflows/ci.yml"),
+            Some("GitHub Workflow")
+        );
+        assert_eq!(
+            find("/project/.github/workflows/sub/ci.yml"),
+            Some("Any YAML")
+        );
+        assert_eq!(find("/project/skip.yml"), None);
+    }
 }
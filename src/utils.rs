@@ -12,41 +12,28 @@ use std::{
 };
 
 pub fn open_file(topic: &str, path: &Path) -> Result<File> {
-    File::open(path).map_err(|error| {
-        log::error!(
-            "Unable to open {} file '{}' due to: {}",
-            topic,
-            path.display(),
-            error
-        );
-        Error::Open
+    File::open(path).map_err(|source| Error::Open {
+        topic: topic.into(),
+        path: path.into(),
+        source,
     })
 }
 
 pub fn read_input(topic: &str, path: &Path, input: &mut dyn Read) -> Result<Vec<u8>> {
     let mut data = Vec::new();
-    if let Err(error) = input.read_to_end(&mut data) {
-        log::error!(
-            "Unable to read {} from '{}' due to: {}",
-            topic,
-            path.display(),
-            error
-        );
-        Err(Error::Read)
-    } else {
-        Ok(data)
-    }
+    input.read_to_end(&mut data).map_err(|source| Error::Read {
+        topic: topic.into(),
+        path: path.into(),
+        source,
+    })?;
+    Ok(data)
 }
 
 pub fn create_file(topic: &str, path: &Path) -> Result<File> {
-    File::create(path).map_err(|error| {
-        log::error!(
-            "Unable to create {} file '{}' due to: {}",
-            topic,
-            path.display(),
-            error
-        );
-        Error::Create
+    File::create(path).map_err(|source| Error::Create {
+        topic: topic.into(),
+        path: path.into(),
+        source,
     })
 }
 
@@ -56,18 +43,13 @@ pub fn write_output(
     output: &mut dyn Write,
     data: impl AsRef<[u8]>,
 ) -> Result<()> {
-    let data = data.as_ref();
-    if let Err(error) = output.write_all(data) {
-        log::error!(
-            "Unable to write {} to '{}' due to: {}",
-            topic,
-            path.display(),
-            error
-        );
-        Err(Error::Write)
-    } else {
-        Ok(())
-    }
+    output
+        .write_all(data.as_ref())
+        .map_err(|source| Error::Write {
+            topic: topic.into(),
+            path: path.into(),
+            source,
+        })
 }
 
 pub fn format_json(topic: &str, data: &json::Value, pretty: bool) -> Result<Vec<u8>> {
@@ -76,8 +58,8 @@ pub fn format_json(topic: &str, data: &json::Value, pretty: bool) -> Result<Vec<
     } else {
         json::to_vec(data)
     }
-    .map_err(|error| {
-        log::error!("Unable to format {} due to: {}", topic, error);
-        Error::Format
+    .map_err(|source| Error::Format {
+        topic: topic.into(),
+        source,
     })
 }
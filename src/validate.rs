@@ -4,13 +4,36 @@ Validating JSON-like data with support for multiple validators
 
 */
 
+mod best_match;
+mod compare;
+mod defaults;
+mod error;
+mod format;
+mod meta;
+mod output;
+mod report;
+mod resolver;
+mod suggest;
+
 pub(self) use crate::{Error, Result};
-pub(self) use std::path::Path;
+use resolver::{Location, Locator};
+
+pub use best_match::Branches;
+pub use compare::Comparison;
+pub use defaults::fill_defaults;
+pub use error::ValidationError;
+pub use format::{FormatMode, Formats};
+pub use output::OutputFormat;
+pub use report::Reporter;
+pub use resolver::Resolver;
+
+#[cfg(feature = "jsonschema-valid")]
+use output::to_pointer;
 
 macro_rules! decl_standards {
     ($(
         $(#[$attr:meta])*
-        $type:ident $name:ident;
+        $type:ident $name:literal $(| $alias:literal)*;
     )*) => {
         /// JSON Schema standard
         #[derive(Clone, Copy, PartialEq, Eq, Debug)]
@@ -28,7 +51,7 @@ macro_rules! decl_standards {
                 Ok(match s {
                     $(
                         $(#[$attr])*
-                        stringify!($name) => Self::$type,
+                        $name $(| $alias)* => Self::$type,
                     )*
                     _ => return Err("unknown"),
                 })
@@ -40,7 +63,7 @@ macro_rules! decl_standards {
                 match self {
                     $(
                         $(#[$attr])*
-                        Self::$type => stringify!($name).fmt(f),
+                        Self::$type => $name.fmt(f),
                     )*
                 }
             }
@@ -51,7 +74,16 @@ macro_rules! decl_standards {
             pub const LIST: &'static [&'static str] = &[
                 $(
                     $(#[$attr])*
-                    stringify!($name),
+                    $name,
+                )*
+            ];
+
+            /// List of all accepted names including hidden aliases
+            pub const NAMES: &'static [&'static str] = &[
+                $(
+                    $(#[$attr])*
+                    $name,
+                    $($alias,)*
                 )*
             ];
         }
@@ -112,8 +144,27 @@ macro_rules! decl_validators {
                 )*
             ];
 
+            /// All available validators
+            pub const ALL: &'static [Self] = &[
+                $(
+                    $(#[$attr])*
+                    Self::$type,
+                )*
+            ];
+
+            /// Check that validator supports standard
+            pub fn supports(&self, std: Standard) -> bool {
+                match self {
+                    $(
+                        $(#[$attr])*
+                        Self::$type => $name::STANDARDS.contains(&std),
+                    )*
+                }
+            }
+
             /// Compile JSON schema
             pub fn compile_schema<'c>(&self, schema: &'c json::Value, std: Option<Standard>) -> Result<CompiledSchema<'c>> {
+                let std = self.choose_standard(schema, std)?;
                 match self {
                     $(
                         $(#[$attr])*
@@ -132,12 +183,48 @@ macro_rules! decl_validators {
         }
 
         impl<'c> CompiledSchema<'c> {
+            /// Validator which compiled schema
+            pub fn validator(&self) -> Validator {
+                match self {
+                    $(
+                        $(#[$attr])*
+                        Self::$type(_) => Validator::$type,
+                    )*
+                }
+            }
+
+            /// Source JSON schema
+            pub fn source(&self) -> &'c json::Value {
+                match self {
+                    $(
+                        $(#[$attr])*
+                        Self::$type(compiled_schema) => compiled_schema.source(),
+                    )*
+                }
+            }
+
             /// Validate JSON data
-            pub fn validate_data(&self, path: &Path, data: &json::Value, quiet: bool) -> Result<u32> {
+            ///
+            /// Errors of custom formats refers `format` keyword instead of rewritten checks.
+            /// Errors of unknown properties and enum values gets suggestions of closest valid names.
+            pub fn validate_data(&self, data: &json::Value) -> Result<Vec<ValidationError>> {
+                let mut errors = match self {
+                    $(
+                        $(#[$attr])*
+                        Self::$type(compiled_schema) => compiled_schema.validate(data)?,
+                    )*
+                };
+                format::restore_errors(self.source(), data, &mut errors);
+                suggest::add_suggestions(self.source(), data, &mut errors);
+                Ok(errors)
+            }
+
+            /// Check that JSON data is valid without collecting errors
+            pub fn is_valid(&self, data: &json::Value) -> Result<bool> {
                 match self {
                     $(
                         $(#[$attr])*
-                        Self::$type(compiled_schema) => compiled_schema.validate(path, data, quiet),
+                        Self::$type(compiled_schema) => compiled_schema.is_valid(data),
                     )*
                 }
             }
@@ -146,9 +233,161 @@ macro_rules! decl_validators {
 }
 
 decl_standards! {
-    Draft4 darft4;
-    Draft6 draft6;
-    Draft7 draft7;
+    // misspelled name is kept for compatibility
+    Draft4 "draft4" | "darft4";
+    Draft6 "draft6";
+    Draft7 "draft7";
+    Draft201909 "draft2019-09";
+    Draft202012 "draft2020-12";
+}
+
+impl Standard {
+    /// Detect standard using `$schema` keyword
+    pub fn detect(schema: &json::Value) -> Option<Self> {
+        Self::from_uri(schema.get("$schema")?.as_str()?)
+    }
+
+    /// Get standard by well-known meta-schema URI or alias
+    pub fn from_uri(uri: &str) -> Option<Self> {
+        let uri = uri.trim();
+        let uri = uri
+            .strip_prefix("https://")
+            .or_else(|| uri.strip_prefix("http://"))
+            .unwrap_or(uri);
+        let uri = uri.trim_end_matches('#').trim_end_matches('/');
+
+        Some(match uri {
+            "json-schema.org/draft-04/schema" | "json-schema.org/draft-04/hyper-schema" => {
+                Self::Draft4
+            }
+            "json-schema.org/draft-06/schema" | "json-schema.org/draft-06/hyper-schema" => {
+                Self::Draft6
+            }
+            "json-schema.org/draft-07/schema" | "json-schema.org/draft-07/hyper-schema" => {
+                Self::Draft7
+            }
+            "json-schema.org/draft/2019-09/schema"
+            | "json-schema.org/draft/2019-09/hyper-schema" => Self::Draft201909,
+            "json-schema.org/draft/2020-12/schema"
+            | "json-schema.org/draft/2020-12/hyper-schema" => Self::Draft202012,
+            "draft-04" => Self::Draft4,
+            "draft-06" => Self::Draft6,
+            "draft-07" => Self::Draft7,
+            "2019-09" => Self::Draft201909,
+            "2020-12" => Self::Draft202012,
+            _ => return uri.parse().ok(),
+        })
+    }
+}
+
+impl Validator {
+    /// Choose standard using explicitly given and declared by `$schema`
+    fn choose_standard(
+        &self,
+        schema: &json::Value,
+        std: Option<Standard>,
+    ) -> Result<Option<Standard>> {
+        let detected = Standard::detect(schema);
+
+        if let Some(std) = std {
+            if let Some(detected) = detected.filter(|detected| *detected != std) {
+                log::warn!(
+                    "Given JSON Schema {} conflicts with {} declared by $schema. Using {}.",
+                    std,
+                    detected,
+                    std
+                );
+            }
+            if !self.supports(std) {
+                return Err(unsupported_standard(*self, std));
+            }
+            log::info!("Using given JSON Schema {}", std);
+            Ok(Some(std))
+        } else if let Some(detected) = detected {
+            if self.supports(detected) {
+                log::info!("Using JSON Schema {} declared by $schema", detected);
+                Ok(Some(detected))
+            } else {
+                log::warn!(
+                    "Validator {} doesn't support JSON Schema {} declared by $schema. Validation results may be inaccurate.",
+                    self,
+                    detected
+                );
+                Ok(None)
+            }
+        } else {
+            if let Some(uri) = schema.get("$schema").and_then(|uri| uri.as_str()) {
+                log::warn!("Unknown JSON Schema dialect '{}'", uri);
+            } else {
+                log::info!("JSON Schema dialect isn't declared. Using validator defaults.");
+            }
+            Ok(None)
+        }
+    }
+}
+
+impl Validator {
+    /// Check schema against bundled meta-schema of its standard
+    ///
+    /// Another validator is used when this one doesn't support the standard
+    /// or cannot compile its meta-schema (valico fails on draft-04 one).
+    /// Unknown keywords aren't errors so it only reported as warnings.
+    pub fn check_schema(
+        &self,
+        schema: &json::Value,
+        std: Option<Standard>,
+    ) -> Result<Vec<ValidationError>> {
+        let std = std.or_else(|| Standard::detect(schema)).unwrap_or_else(|| {
+            log::info!("JSON Schema dialect isn't declared. Checking it as draft7.");
+            Standard::Draft7
+        });
+
+        for pointer in meta::unknown_keywords(schema, std) {
+            log::warn!(
+                "Unknown keyword at '#{}' of JSON Schema {} (maybe typo?)",
+                pointer,
+                std
+            );
+        }
+
+        let meta_schema = meta::meta_schema(std);
+        let validators = std::iter::once(*self)
+            .chain(
+                Self::ALL
+                    .iter()
+                    .copied()
+                    .filter(|validator| validator != self),
+            )
+            .filter(|validator| validator.supports(std));
+        let mut failure = None;
+        for validator in validators {
+            match validator.compile_schema(&meta_schema, Some(std)) {
+                Ok(compiled) => {
+                    if validator != *self {
+                        log::info!("Using validator {} to check JSON Schema {}", validator, std);
+                    }
+                    return compiled.validate_data(schema);
+                }
+                Err(error) => {
+                    log::debug!(
+                        "Validator {} cannot compile meta-schema: {}",
+                        validator,
+                        error
+                    );
+                    failure.get_or_insert(error);
+                }
+            }
+        }
+        Err(failure.unwrap_or_else(|| unsupported_standard(*self, std)))
+    }
+}
+
+/// Report standard which isn't supported by validator
+fn unsupported_standard(validator: Validator, std: Standard) -> Error {
+    Error::Compile(format!(
+        "Validator {} doesn't support JSON Schema {}. Try use another validator with -v option.",
+        validator, std
+    ))
 }
 
 decl_validators! {
@@ -159,3 +398,100 @@ decl_validators! {
     #[cfg(feature = "jsonschema-valid")]
     JsonSchemaValid jsonschema_valid;
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn standard_names() {
+        for name in Standard::LIST {
+            let std: Standard = name.parse().unwrap();
+            assert_eq!(&std.to_string(), name);
+        }
+        assert_eq!("draft2020-12".parse(), Ok(Standard::Draft202012));
+        // hidden alias of former name
+        assert_eq!("darft4".parse(), Ok(Standard::Draft4));
+        assert!(!Standard::LIST.contains(&"darft4"));
+        assert!(Standard::NAMES.contains(&"darft4"));
+        assert!("draft5".parse::<Standard>().is_err());
+    }
+
+    #[test]
+    fn detect_standard() {
+        let detect = |uri: &str| Standard::detect(&json::json!({ "$schema": uri }));
+        assert_eq!(
+            detect("http://json-schema.org/draft-04/schema#"),
+            Some(Standard::Draft4)
+        );
+        assert_eq!(
+            detect("https://json-schema.org/draft-07/schema"),
+            Some(Standard::Draft7)
+        );
+        assert_eq!(
+            detect("https://json-schema.org/draft/2019-09/schema"),
+            Some(Standard::Draft201909)
+        );
+        assert_eq!(
+            detect("https://json-schema.org/draft/2020-12/hyper-schema#"),
+            Some(Standard::Draft202012)
+        );
+        assert_eq!(detect("draft6"), Some(Standard::Draft6));
+        assert_eq!(detect("2020-12"), Some(Standard::Draft202012));
+        assert_eq!(detect("https://example.com/schema"), None);
+        assert_eq!(Standard::detect(&json::json!({})), None);
+    }
+
+    #[test]
+    #[cfg(any(feature = "jsonschema", feature = "jsonschema-valid"))]
+    fn check_draft4_schema() {
+        let validator: Validator = Validator::LIST[0].parse().unwrap();
+        let schema = json::json!({
+            "$schema": "http://json-schema.org/draft-04/schema#",
+            "type": "number",
+            "minimum": 0,
+            "exclusiveMinimum": true
+        });
+        assert!(validator.check_schema(&schema, None).unwrap().is_empty());
+
+        // draft-04 has boolean exclusive limits
+        let schema = json::json!({ "type": "number", "exclusiveMinimum": 0 });
+        let errors = validator
+            .check_schema(&schema, Some(Standard::Draft4))
+            .unwrap();
+        assert!(errors
+            .iter()
+            .any(|error| error.instance_path == "/exclusiveMinimum"));
+    }
+
+    #[test]
+    #[cfg_attr(not(feature = "valico"), allow(unused_variables))]
+    fn unsupported_standards() {
+        let schema = json::json!({ "type": "string" });
+
+        #[cfg(feature = "jsonschema")]
+        assert!(Validator::JsonSchema
+            .compile_schema(&schema, Some(Standard::Draft202012))
+            .is_ok());
+        #[cfg(feature = "jsonschema-valid")]
+        assert!(Validator::JsonSchemaValid
+            .compile_schema(&schema, Some(Standard::Draft201909))
+            .is_err());
+        #[cfg(feature = "valico")]
+        assert!(Validator::Valico
+            .compile_schema(&schema, Some(Standard::Draft202012))
+            .is_err());
+        #[cfg(feature = "valico")]
+        assert!(Validator::Valico
+            .compile_schema(&schema, Some(Standard::Draft7))
+            .is_ok());
+
+        // declared but unsupported standard isn't an error
+        let schema = json::json!({
+            "$schema": "https://json-schema.org/draft/2020-12/schema",
+            "type": "string"
+        });
+        #[cfg(feature = "valico")]
+        assert!(Validator::Valico.compile_schema(&schema, None).is_ok());
+    }
+}
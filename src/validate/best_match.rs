@@ -0,0 +1,275 @@
+/*!
+
+Explaining `oneOf` and `anyOf` failures by its best matching branch
+
+*/
+
+use super::{
+    resolver::DATA_KEYWORDS, suggest::keyword_parent, CompiledSchema, Locator, Resolver, Standard,
+    ValidationError, Validator,
+};
+use json::Value;
+use std::{cmp::Reverse, collections::HashMap, sync::OnceLock};
+
+/// Keywords which fails when none of its subschemas is matched
+const KEYWORDS: &[&str] = &["oneOf", "anyOf"];
+
+/// Keyword is identified by address of subschema which contains it and its name
+type Key = (usize, &'static str);
+
+/// Branches of `oneOf` and `anyOf` keywords of compiled schema
+///
+/// Validators reports such failures without reasons so each branch is validated
+/// separately. Branches is narrowed to standalone schemas on first use.
+pub struct Branches<'s> {
+    validator: Validator,
+    std: Option<Standard>,
+    locator: Locator<'s>,
+    keywords: HashMap<Key, Keyword>,
+}
+
+struct Keyword {
+    /// Schema path of keyword without references
+    path: String,
+    schemas: Vec<OnceLock<Option<Value>>>,
+}
+
+impl<'s> Branches<'s> {
+    /// Collect branches of schema which is compiled using given standard
+    pub fn new(schema: &CompiledSchema<'s>, std: Option<Standard>) -> Self {
+        let mut keywords = HashMap::new();
+        collect_keywords(schema.source(), &mut String::new(), &mut keywords);
+        Self {
+            validator: schema.validator(),
+            std,
+            locator: Locator::new(schema.source(), None),
+            keywords,
+        }
+    }
+
+    /// Make matcher which compiles each branch once
+    pub(super) fn matcher(&'s self) -> Matcher<'s> {
+        Matcher {
+            branches: self,
+            compiled: self
+                .keywords
+                .iter()
+                .map(|(key, keyword)| {
+                    let compiled = keyword.schemas.iter().map(|_| OnceLock::new()).collect();
+                    (*key, compiled)
+                })
+                .collect(),
+        }
+    }
+}
+
+fn collect_keywords(schema: &Value, path: &mut String, keywords: &mut HashMap<Key, Keyword>) {
+    let len = path.len();
+    match schema {
+        Value::Object(object) => {
+            for keyword in KEYWORDS {
+                if let Some(Value::Array(branches)) = object.get(*keyword) {
+                    let key = (schema as *const Value as usize, *keyword);
+                    let keyword = Keyword {
+                        path: format!("{}/{}", path, keyword),
+                        schemas: branches.iter().map(|_| OnceLock::new()).collect(),
+                    };
+                    keywords.insert(key, keyword);
+                }
+            }
+            for (key, value) in object {
+                if !DATA_KEYWORDS.contains(&key.as_str()) {
+                    path.push('/');
+                    path.push_str(&key.replace('~', "~0").replace('/', "~1"));
+                    collect_keywords(value, path, keywords);
+                    path.truncate(len);
+                }
+            }
+        }
+        Value::Array(array) => {
+            for (index, value) in array.iter().enumerate() {
+                path.push_str(&format!("/{}", index));
+                collect_keywords(value, path, keywords);
+                path.truncate(len);
+            }
+        }
+        _ => (),
+    }
+}
+
+/// Explanation of failed `oneOf` or `anyOf`
+pub struct Explanation {
+    /// Index of best matching branch
+    pub best: usize,
+    /// Errors of each branch relative to root schema and data
+    pub branches: Vec<Vec<ValidationError>>,
+}
+
+/// Choosing best matching branches with compiled branches cache
+pub(super) struct Matcher<'b> {
+    branches: &'b Branches<'b>,
+    compiled: HashMap<Key, Vec<OnceLock<Option<CompiledSchema<'b>>>>>,
+}
+
+impl Matcher<'_> {
+    /// Validate branches of failed keyword and choose best one
+    ///
+    /// Nothing is chosen when some branch is valid (i.e. `oneOf` matches many).
+    pub fn explain(&self, data: &Value, error: &ValidationError) -> Option<Explanation> {
+        let name = *KEYWORDS.iter().find(|keyword| **keyword == error.keyword)?;
+        let parent = keyword_parent(&self.branches.locator, &error.schema_path, name)?;
+        let key = (parent.schema as *const Value as usize, name);
+        let keyword = self.branches.keywords.get(&key)?;
+        let instance = data.pointer(&error.instance_path)?;
+
+        let mut results = Vec::with_capacity(keyword.schemas.len());
+        for (index, (schema, compiled)) in
+            keyword.schemas.iter().zip(&self.compiled[&key]).enumerate()
+        {
+            let compiled = compiled
+                .get_or_init(|| {
+                    let schema = schema
+                        .get_or_init(|| {
+                            let path = format!("{}/{}", keyword.path, index);
+                            Resolver::narrow(self.branches.locator.root().schema, None, &path)
+                        })
+                        .as_ref()?;
+                    self.branches
+                        .validator
+                        .compile_schema(schema, self.branches.std)
+                        .map_err(|error| {
+                            log::debug!("Unable to compile {} branch due to: {}", index, error)
+                        })
+                        .ok()
+                })
+                .as_ref()?;
+            let errors = compiled
+                .validate_data(instance)
+                .map_err(|error| {
+                    log::debug!("Unable to validate {} branch due to: {}", index, error)
+                })
+                .ok()?;
+            if errors.is_empty() {
+                return None;
+            }
+            results.push(rebase(error, index, errors));
+        }
+
+        let depth = depth(&error.instance_path);
+        let (best, _) = results
+            .iter()
+            .enumerate()
+            .min_by_key(|(_, errors)| rank(depth, errors))?;
+        Some(Explanation {
+            best,
+            branches: results,
+        })
+    }
+}
+
+/// Rank branch errors where lower is better
+///
+/// Branch which fails on type of instance itself or on `const` or `enum` of its
+/// properties (i.e. discriminator) is worse. Then branch which errors are deeper in
+/// data is better because it matched more. Then branch with fewer errors is better.
+fn rank(depth: usize, errors: &[ValidationError]) -> (bool, bool, Reverse<usize>, usize) {
+    let type_mismatch = errors
+        .iter()
+        .any(|error| error.keyword == "type" && self::depth(&error.instance_path) == depth);
+    let discriminator_mismatch = errors.iter().any(|error| {
+        (error.keyword == "const" || error.keyword == "enum")
+            && self::depth(&error.instance_path) == depth + 1
+    });
+    let deepest = errors
+        .iter()
+        .map(|error| self::depth(&error.instance_path))
+        .max()
+        .unwrap_or_default();
+    (
+        type_mismatch,
+        discriminator_mismatch,
+        Reverse(deepest),
+        errors.len(),
+    )
+}
+
+fn depth(pointer: &str) -> usize {
+    pointer.matches('/').count()
+}
+
+/// Make branch errors locations relative to root schema and data
+fn rebase(
+    failure: &ValidationError,
+    index: usize,
+    errors: Vec<ValidationError>,
+) -> Vec<ValidationError> {
+    errors
+        .into_iter()
+        .map(|mut error| {
+            error.instance_path = format!("{}{}", failure.instance_path, error.instance_path);
+            error.schema_path = format!("{}/{}{}", failure.schema_path, index, error.schema_path);
+            error
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn best_matching_branch() {
+        let schema = json::json!({
+            "type": "object",
+            "properties": {
+                "volumes": {
+                    "type": "array",
+                    "items": {
+                        "oneOf": [
+                            { "$ref": "#/definitions/secret" },
+                            {
+                                "type": "object",
+                                "properties": {
+                                    "kind": { "const": "configMap" },
+                                    "name": { "type": "string" }
+                                },
+                                "required": ["kind", "name"]
+                            },
+                            { "type": "string" }
+                        ]
+                    }
+                }
+            },
+            "definitions": {
+                "secret": {
+                    "type": "object",
+                    "properties": {
+                        "kind": { "const": "secret" },
+                        "secretName": { "type": "string" }
+                    },
+                    "required": ["kind", "secretName"]
+                }
+            }
+        });
+        let data = json::json!({ "volumes": [{ "kind": "configMap", "name": 1 }] });
+
+        for validator in Validator::ALL {
+            let compiled = validator.compile_schema(&schema, None).unwrap();
+            let errors = compiled.validate_data(&data).unwrap();
+            assert_eq!(errors.len(), 1, "{}: {:?}", validator, errors);
+            assert_eq!(errors[0].keyword, "oneOf");
+
+            let branches = Branches::new(&compiled, None);
+            let explanation = branches.matcher().explain(&data, &errors[0]).unwrap();
+            assert_eq!(explanation.branches.len(), 3);
+            assert_eq!(explanation.best, 1);
+            let best = &explanation.branches[1];
+            assert_eq!(best.len(), 1, "{}: {:?}", validator, best);
+            assert_eq!(best[0].instance_path, "/volumes/0/name");
+            assert_eq!(best[0].keyword, "type");
+            assert!(best[0]
+                .schema_path
+                .starts_with("/properties/volumes/items/oneOf/1/"));
+        }
+    }
+}
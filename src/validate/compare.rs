@@ -0,0 +1,148 @@
+/*!
+
+Comparing validation results of different validators
+
+*/
+
+use super::{ValidationError, Validator};
+use std::{collections::BTreeSet, fmt::Write, path::Path};
+
+/// Results of validating same data by several validators
+#[derive(Default)]
+pub struct Comparison {
+    results: Vec<(Validator, Vec<ValidationError>)>,
+}
+
+/// Error identity which doesn't depend on validator-specific messages
+fn error_key(error: &ValidationError) -> (&str, &str) {
+    (&error.instance_path, &error.keyword)
+}
+
+impl Comparison {
+    /// Add validation results of validator
+    pub fn push(&mut self, validator: Validator, errors: Vec<ValidationError>) {
+        self.results.push((validator, errors));
+    }
+
+    fn error_set(errors: &[ValidationError]) -> BTreeSet<(&str, &str)> {
+        errors.iter().map(error_key).collect()
+    }
+
+    /// Check that all validators gives same verdict and reports same errors
+    pub fn agree(&self) -> bool {
+        let mut sets = self
+            .results
+            .iter()
+            .map(|(_, errors)| Self::error_set(errors));
+        if let Some(first) = sets.next() {
+            sets.all(|set| set == first)
+        } else {
+            true
+        }
+    }
+
+    /// Make text report which highlights disagreements
+    ///
+    /// Errors are listed only when they isn't reported by all other validators.
+    pub fn report(&self, path: &Path, verbose: bool) -> String {
+        let mut report = String::new();
+
+        if self.agree() {
+            let valid = self.results.iter().all(|(_, errors)| errors.is_empty());
+            let _ = writeln!(
+                report,
+                "{}: validators agree that data is {}",
+                path.display(),
+                if valid { "valid" } else { "not valid" }
+            );
+            return report;
+        }
+
+        let _ = writeln!(report, "{}: validators disagree", path.display());
+
+        let sets = self
+            .results
+            .iter()
+            .map(|(_, errors)| Self::error_set(errors))
+            .collect::<Vec<_>>();
+
+        for (index, (validator, errors)) in self.results.iter().enumerate() {
+            if errors.is_empty() {
+                let _ = writeln!(report, "  {}: data is valid", validator);
+            } else {
+                let _ = writeln!(
+                    report,
+                    "  {}: data is not valid ({} errors)",
+                    validator,
+                    errors.len()
+                );
+            }
+            for error in errors {
+                let key = error_key(error);
+                let common = sets
+                    .iter()
+                    .enumerate()
+                    .all(|(other, set)| other == index || set.contains(&key));
+                if !common {
+                    let _ = writeln!(report, "    {}", error);
+                    if verbose {
+                        let _ = writeln!(
+                            report,
+                            "      keyword '{}' at schema path '{}'",
+                            error.keyword, error.schema_path
+                        );
+                    }
+                }
+            }
+        }
+
+        report
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn comparison(results: Vec<Vec<ValidationError>>) -> Comparison {
+        let mut comparison = Comparison::default();
+        for (validator, errors) in Validator::ALL.iter().cycle().zip(results) {
+            comparison.push(*validator, errors);
+        }
+        comparison
+    }
+
+    #[test]
+    fn compare_results() {
+        let path = Path::new("data.yaml");
+        let error = ValidationError::new("/a", "/properties/a/type", "Invalid type");
+        let other = ValidationError::new("/a", "/properties/a/type", "Wrong type");
+        let first = Validator::ALL[0];
+        let second = Validator::ALL[1 % Validator::ALL.len()];
+
+        let results = comparison(vec![vec![], vec![]]);
+        assert!(results.agree());
+        assert_eq!(
+            results.report(path, false),
+            "data.yaml: validators agree that data is valid\n"
+        );
+
+        // messages doesn't matter
+        let results = comparison(vec![vec![error.clone()], vec![other]]);
+        assert!(results.agree());
+        assert_eq!(
+            results.report(path, false),
+            "data.yaml: validators agree that data is not valid\n"
+        );
+
+        let results = comparison(vec![vec![error], vec![]]);
+        assert!(!results.agree());
+        assert_eq!(
+            results.report(path, false),
+            format!(
+                "data.yaml: validators disagree\n  {}: data is not valid (1 errors)\n    #/a: Invalid type\n  {}: data is valid\n",
+                first, second
+            )
+        );
+    }
+}
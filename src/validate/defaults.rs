@@ -0,0 +1,215 @@
+/*!
+
+Filling data with default values from schema
+
+*/
+
+use super::{Location, Locator};
+use json::Value;
+
+/// Insert defaults of missing properties into data
+///
+/// Schema is walked alongside data following `properties`, `items`, `prefixItems`,
+/// `additionalItems`, `allOf` and `$ref`s (including resources embedded by bundling).
+/// Existing values is never replaced. Default of recursive schema is inserted
+/// only once along each path of data (i.e. not into itself).
+pub fn fill_defaults(schema: &Value, data: &mut Value) {
+    let locator = Locator::new(schema, None);
+    Walker { locator: &locator }.fill(locator.root(), data, &mut Vec::new(), &mut Vec::new());
+}
+
+struct Walker<'l, 's> {
+    locator: &'l Locator<'s>,
+}
+
+impl<'s> Walker<'_, 's> {
+    /// Fill data using subschema
+    ///
+    /// Subschemas which is already applied to the same data is skipped to
+    /// prevent infinite recursion through references. Ancestors are subschemas
+    /// of properties and items on the path to data.
+    fn fill(
+        &self,
+        location: Location<'s>,
+        data: &mut Value,
+        ancestors: &mut Vec<&'s Value>,
+        applied: &mut Vec<&'s Value>,
+    ) {
+        let schema = location.schema;
+        if !schema.is_object() || contains(applied, schema) {
+            return;
+        }
+        applied.push(schema);
+
+        if let Some(target) = self.locator.follow(&location) {
+            self.fill(target, data, ancestors, applied);
+        }
+        for subschema in self.subschemas(&location, "allOf") {
+            self.fill(subschema, data, ancestors, applied);
+        }
+
+        match data {
+            Value::Object(data) => {
+                let properties = self.properties(&location);
+                for (name, property) in properties {
+                    if !data.contains_key(name) && !contains(ancestors, property.schema) {
+                        if let Some(default) = self.default(property.clone()) {
+                            data.insert(name.clone(), default.clone());
+                        }
+                    }
+                    if let Some(value) = data.get_mut(name) {
+                        self.descend(property, value, ancestors);
+                    }
+                }
+            }
+            Value::Array(items) => {
+                let (tuple, rest) = if schema.get("prefixItems").is_some() {
+                    ("prefixItems", "items")
+                } else if schema.get("items").is_some_and(Value::is_array) {
+                    ("items", "additionalItems")
+                } else {
+                    ("prefixItems", "items")
+                };
+                let tuple = self.subschemas(&location, tuple);
+                let rest = self.locator.child(&location, rest);
+                for (index, item) in items.iter_mut().enumerate() {
+                    if let Some(subschema) = tuple.get(index).cloned().or_else(|| rest.clone()) {
+                        self.descend(subschema, item, ancestors);
+                    }
+                }
+            }
+            _ => (),
+        }
+    }
+
+    /// Fill nested data using subschema of its property or item
+    fn descend(&self, location: Location<'s>, data: &mut Value, ancestors: &mut Vec<&'s Value>) {
+        ancestors.push(location.schema);
+        self.fill(location, data, ancestors, &mut Vec::new());
+        ancestors.pop();
+    }
+
+    /// Get default value of subschema following references
+    fn default(&self, location: Location<'s>) -> Option<&'s Value> {
+        self.locator
+            .find(location, |location| location.schema.get("default"))
+    }
+
+    /// Locations of subschemas in `properties` by its names
+    fn properties(&self, location: &Location<'s>) -> Vec<(&'s String, Location<'s>)> {
+        let properties = match self.locator.child(location, "properties") {
+            Some(properties) => properties,
+            None => return Vec::new(),
+        };
+        properties
+            .schema
+            .as_object()
+            .into_iter()
+            .flatten()
+            .filter_map(|(name, _)| Some((name, self.locator.child(&properties, name)?)))
+            .collect()
+    }
+
+    /// Locations of subschemas in array keyword
+    fn subschemas(&self, location: &Location<'s>, keyword: &str) -> Vec<Location<'s>> {
+        let keyword = match self.locator.child(location, keyword) {
+            Some(keyword) => keyword,
+            None => return Vec::new(),
+        };
+        let count = keyword.schema.as_array().map_or(0, Vec::len);
+        (0..count)
+            .filter_map(|index| self.locator.child(&keyword, &index.to_string()))
+            .collect()
+    }
+}
+
+fn contains(schemas: &[&Value], schema: &Value) -> bool {
+    schemas.iter().any(|other| std::ptr::eq(*other, schema))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn fill_missing_properties() {
+        let schema = json::json!({
+            "$id": "https://example.com/app.json",
+            "properties": {
+                "replicas": { "type": "integer", "default": 1 },
+                "policy": { "$ref": "#/definitions/policy" },
+                "mode": { "$ref": "#/definitions/run%20mode" },
+                "spec": {
+                    "default": {},
+                    "properties": {
+                        "port": { "default": 80 },
+                        "name": { "default": "app" }
+                    }
+                },
+                "containers": {
+                    "type": "array",
+                    "items": { "$ref": "common.json#/definitions/container" }
+                },
+                "pair": {
+                    "items": [{ "default": "ignored" }, { "properties": { "b": { "default": 2 } } }],
+                    "additionalItems": { "properties": { "c": { "default": 3 } } }
+                }
+            },
+            "allOf": [{ "properties": { "debug": { "default": false } } }],
+            "definitions": {
+                "policy": { "enum": ["Always", "Never"], "default": "Always" },
+                "run mode": { "default": "fast" },
+                "common": {
+                    "$id": "https://example.com/common.json",
+                    "definitions": {
+                        "container": {
+                            "properties": { "image": { "$ref": "#/definitions/image" } }
+                        },
+                        "image": { "default": "alpine" }
+                    }
+                }
+            }
+        });
+        let mut data = json::json!({
+            "spec": { "name": "web" },
+            "containers": [{}, { "image": "debian" }],
+            "pair": [null, {}, {}],
+            "debug": true
+        });
+        fill_defaults(&schema, &mut data);
+        assert_eq!(
+            data,
+            json::json!({
+                "spec": { "name": "web", "port": 80 },
+                "containers": [{ "image": "alpine" }, { "image": "debian" }],
+                "pair": [null, { "b": 2 }, { "c": 3 }],
+                "debug": true,
+                "replicas": 1,
+                "policy": "Always",
+                "mode": "fast"
+            })
+        );
+
+        let mut data = json::json!({});
+        fill_defaults(&schema, &mut data);
+        assert_eq!(data["spec"], json::json!({ "port": 80, "name": "app" }));
+    }
+
+    #[test]
+    fn recursive_references() {
+        let schema = json::json!({
+            "properties": {
+                "child": { "$ref": "#" },
+                "name": { "default": "x" },
+                "next": { "$ref": "#/properties/child", "default": {} }
+            },
+            "allOf": [{ "$ref": "#" }, { "$ref": "#" }]
+        });
+        let mut data = json::json!({ "child": {} });
+        fill_defaults(&schema, &mut data);
+        assert_eq!(data["name"], "x");
+        assert_eq!(data["child"]["name"], "x");
+        // default of recursive schema isn't inserted into itself
+        assert_eq!(data["next"], json::json!({ "name": "x" }));
+    }
+}
@@ -0,0 +1,75 @@
+/*!
+
+Validation error which is common for all validators
+
+*/
+
+use std::fmt::{Display, Formatter, Result as FmtResult};
+
+/// Single validation error
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ValidationError {
+    /// JSON pointer to the failed value in data
+    pub instance_path: String,
+    /// JSON pointer to the failed keyword through the evaluation path
+    pub schema_path: String,
+    /// Failed keyword
+    pub keyword: String,
+    /// Human-readable error message
+    pub message: String,
+    /// Validator-specific error details
+    pub detail: Option<String>,
+}
+
+impl ValidationError {
+    /// Create error using keyword which is the last segment of schema path
+    pub fn new(
+        instance_path: impl Into<String>,
+        schema_path: impl Into<String>,
+        message: impl Into<String>,
+    ) -> Self {
+        let schema_path = schema_path.into();
+        let keyword = schema_path
+            .rsplit('/')
+            .next()
+            .unwrap_or_default()
+            .to_string();
+        Self {
+            instance_path: instance_path.into(),
+            schema_path,
+            keyword,
+            message: message.into(),
+            detail: None,
+        }
+    }
+
+    /// Set validator-specific details
+    pub fn with_detail(mut self, detail: impl Into<String>) -> Self {
+        self.detail = Some(detail.into());
+        self
+    }
+}
+
+impl Display for ValidationError {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        '#'.fmt(f)?;
+        self.instance_path.fmt(f)?;
+        ": ".fmt(f)?;
+        self.message.fmt(f)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn keyword_from_schema_path() {
+        let error = ValidationError::new("/a", "/properties/a/type", "Invalid type");
+        assert_eq!(error.keyword, "type");
+        assert_eq!(error.to_string(), "#/a: Invalid type");
+
+        let error = ValidationError::new("", "", "Invalid");
+        assert_eq!(error.keyword, "");
+    }
+}
@@ -0,0 +1,368 @@
+/*!
+
+Custom `format` keyword validators
+
+Custom formats are defined by regular expressions. The schema is rewritten before
+compiling so each `format` which refers custom format is replaced by `pattern`.
+This way custom formats works in same way with any validator. Errors of such checks
+are reported as errors of `format` keyword.
+
+*/
+
+use super::{Locator, ValidationError};
+use crate::{utils, Error, Format, Result};
+use json::{json, Value};
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
+/// Built-in extra formats which isn't supported by validators
+const BUILTIN_FORMATS: &[(&str, &str)] = &[
+    (
+        "semver",
+        r"^(0|[1-9][0-9]*)\.(0|[1-9][0-9]*)\.(0|[1-9][0-9]*)(-(0|[1-9][0-9]*|[0-9]*[a-zA-Z-][0-9a-zA-Z-]*)(\.(0|[1-9][0-9]*|[0-9]*[a-zA-Z-][0-9a-zA-Z-]*))*)?(\+[0-9a-zA-Z-]+(\.[0-9a-zA-Z-]+)*)?$",
+    ),
+    (
+        "k8s-quantity",
+        r"^[+-]?([0-9]+(\.[0-9]*)?|\.[0-9]+)([KMGTPE]i|[numkMGTPE]|[eE][+-]?[0-9]+)?$",
+    ),
+    // ISO 8601 durations as well as Go-style durations like `1h30m`
+    (
+        "duration",
+        r"^(P([0-9]+W|([0-9]+Y)?([0-9]+M)?([0-9]+D)?(T([0-9]+H)?([0-9]+M)?([0-9]+(\.[0-9]+)?S)?)?)|[+-]?(([0-9]+(\.[0-9]*)?|\.[0-9]+)(ns|us|µs|ms|s|m|h))+|0)$",
+    ),
+    (
+        "cron",
+        r"^(@(annually|yearly|monthly|weekly|daily|midnight|hourly|reboot)|@every [0-9a-z.]+|[0-9A-Za-z*?#,/-]+( +[0-9A-Za-z*?#,/-]+){4,6})$",
+    ),
+];
+
+/// Keyword which marks checks of custom formats in rewritten schema
+const FORMAT_KEYWORD: &str = "x-jsonst-format";
+
+/// Treatment of `format` keyword
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum FormatMode {
+    /// Values must conform formats
+    Assertion,
+    /// Formats is informative only
+    Annotation,
+}
+
+impl std::str::FromStr for FormatMode {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        Ok(match s {
+            "assertion" => Self::Assertion,
+            "annotation" => Self::Annotation,
+            _ => return Err("unknown"),
+        })
+    }
+}
+
+impl std::fmt::Display for FormatMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::Assertion => "assertion",
+            Self::Annotation => "annotation",
+        }
+        .fmt(f)
+    }
+}
+
+impl FormatMode {
+    /// List of all variants
+    pub const LIST: &'static [&'static str] = &["assertion", "annotation"];
+}
+
+/// Registry of custom formats
+pub struct Formats {
+    /// Regular expressions of formats by names
+    patterns: BTreeMap<String, String>,
+    mode: FormatMode,
+}
+
+impl Formats {
+    /// Create registry with built-in formats and formats defined in files
+    pub fn new(mode: FormatMode, files: &[PathBuf]) -> Result<Self> {
+        let mut formats = Self::builtin(mode);
+        for path in files {
+            formats.load_file(path)?;
+        }
+        Ok(formats)
+    }
+
+    /// Create registry with built-in formats only
+    pub fn builtin(mode: FormatMode) -> Self {
+        Self {
+            patterns: BTreeMap::from_iter(
+                BUILTIN_FORMATS
+                    .iter()
+                    .map(|(name, pattern)| (name.to_string(), pattern.to_string())),
+            ),
+            mode,
+        }
+    }
+
+    /// Add format defined by regular expression
+    ///
+    /// Format with same name will be overridden.
+    pub fn insert(&mut self, name: impl Into<String>, pattern: impl Into<String>) {
+        self.patterns.insert(name.into(), pattern.into());
+    }
+
+    /// Load formats from file with object of names and regular expressions
+    ///
+    /// File which cannot be read or parsed is an error while invalid formats
+    /// in it are skipped with warnings.
+    pub fn load_file(&mut self, path: &Path) -> Result<()> {
+        let topic = "formats";
+        let mut file = utils::open_file(topic, path)?;
+        let data = utils::read_input(topic, path, &mut file)?;
+        let format = Format::from_path(path).unwrap_or(Format::Json);
+        let definitions = match format.parse_data(topic, path, &data)? {
+            Value::Object(definitions) => definitions,
+            _ => {
+                return Err(Error::Parse {
+                    topic: topic.into(),
+                    format,
+                    path: path.into(),
+                    source: "must contain object of names and regular expressions".into(),
+                    schema: false,
+                })
+            }
+        };
+        for (name, pattern) in definitions {
+            if let Value::String(pattern) = pattern {
+                if let Err(error) = regex::Regex::new(&pattern) {
+                    log::warn!(
+                        "Format '{}' in '{}' has invalid regular expression: {}",
+                        name,
+                        path.display(),
+                        error
+                    );
+                    continue;
+                }
+                log::debug!("Found format '{}' in '{}'", name, path.display());
+                self.insert(name, pattern);
+            } else {
+                log::warn!(
+                    "Format '{}' in '{}' isn't a regular expression string",
+                    name,
+                    path.display()
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// Rewrite schema to apply custom formats and format mode
+    ///
+    /// When formats is annotations all `format` keywords are removed so no validator checks it.
+    pub fn apply(&self, schema: &json::Value) -> json::Value {
+        let mut schema = schema.clone();
+        self.rewrite(&mut schema);
+        schema
+    }
+
+    fn rewrite(&self, schema: &mut Value) {
+        let object = if let Value::Object(object) = schema {
+            object
+        } else {
+            return;
+        };
+        for (key, value) in object.iter_mut() {
+            match (key.as_str(), value) {
+                (
+                    "properties" | "patternProperties" | "definitions" | "$defs"
+                    | "dependentSchemas" | "dependencies",
+                    Value::Object(schemas),
+                ) => schemas.values_mut().for_each(|schema| self.rewrite(schema)),
+                ("items" | "prefixItems" | "allOf" | "anyOf" | "oneOf", Value::Array(schemas)) => {
+                    schemas.iter_mut().for_each(|schema| self.rewrite(schema))
+                }
+                (
+                    "items"
+                    | "additionalItems"
+                    | "additionalProperties"
+                    | "not"
+                    | "if"
+                    | "then"
+                    | "else"
+                    | "contains"
+                    | "propertyNames"
+                    | "unevaluatedItems"
+                    | "unevaluatedProperties"
+                    | "contentSchema",
+                    value,
+                ) => self.rewrite(value),
+                _ => (),
+            }
+        }
+
+        let name = match object.get("format") {
+            Some(Value::String(name)) => name.clone(),
+            _ => return,
+        };
+        if self.mode == FormatMode::Annotation {
+            object.remove("format");
+        } else if let Some(pattern) = self.patterns.get(&name) {
+            object.remove("format");
+            // existing pattern shouldn't be replaced
+            let check = json!({
+                "description": format!("Value must be valid '{}'", name),
+                "pattern": pattern,
+                FORMAT_KEYWORD: name,
+            });
+            if let Some(Value::Array(checks)) = object.get_mut("allOf") {
+                checks.push(check);
+            } else {
+                object.insert("allOf".into(), Value::Array(vec![check]));
+            }
+        }
+    }
+}
+
+/// Make errors of custom formats checks refer `format` keyword
+///
+/// Checks are found by marks in rewritten schema. Original error message is kept as detail.
+pub fn restore_errors(schema: &Value, data: &Value, errors: &mut [ValidationError]) {
+    let locator = Locator::new(schema, None);
+    for error in errors {
+        let (parent, name) = match error
+            .schema_path
+            .strip_suffix("/pattern")
+            .and_then(|path| find_check(&locator, path))
+        {
+            Some(check) => check,
+            None => continue,
+        };
+        let message = match data.pointer(&error.instance_path) {
+            Some(value) => format!("{} is not a '{}'", value, name),
+            None => format!("Value must be valid '{}'", name),
+        };
+        error.schema_path = format!("{}/format", parent);
+        error.keyword = "format".into();
+        let pattern_message = std::mem::replace(&mut error.message, message);
+        error.detail.get_or_insert(pattern_message);
+    }
+}
+
+/// Find schema path of rewritten `format` and its name by path of failed `pattern`
+///
+/// Some validators (valico) omits `allOf` in paths so schema itself may hold the check.
+fn find_check<'s>(locator: &Locator<'s>, path: &str) -> Option<(String, &'s str)> {
+    let schema = locator.locate(path)?.schema;
+    if let Some(name) = schema.get(FORMAT_KEYWORD).and_then(Value::as_str) {
+        let (parent, _) = path.rsplit_once("/allOf/")?;
+        return Some((parent.into(), name));
+    }
+    if schema.get("pattern").is_some() {
+        return None;
+    }
+    schema
+        .get("allOf")?
+        .as_array()?
+        .iter()
+        .find_map(|check| check.get(FORMAT_KEYWORD)?.as_str())
+        .map(|name| (path.into(), name))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Validator;
+
+    #[test]
+    fn custom_formats() {
+        let mut formats = Formats::builtin(FormatMode::Assertion);
+        formats.insert("upper", "^[A-Z]+$");
+        let schema = formats.apply(&json!({
+            "type": "object",
+            "properties": {
+                "version": { "format": "semver" },
+                "memory": { "format": "k8s-quantity" },
+                "timeout": { "format": "duration" },
+                "schedule": { "format": "cron" },
+                "name": { "format": "upper", "minLength": 2 },
+                "format": { "type": "string" }
+            }
+        }));
+        assert_eq!(schema["properties"]["format"], json!({ "type": "string" }));
+        assert!(schema["properties"]["version"].get("format").is_none());
+
+        let valid = json!({
+            "version": "1.2.3-rc.1+build.5",
+            "memory": "512Mi",
+            "timeout": "1h30m",
+            "schedule": "*/5 * * * 1-5",
+            "name": "ABC"
+        });
+        let invalid = json!({
+            "version": "1.2",
+            "memory": "512MB",
+            "timeout": "1 hour",
+            "schedule": "every day",
+            "name": "abc"
+        });
+        for validator in Validator::ALL {
+            let compiled = validator.compile_schema(&schema, None).unwrap();
+            assert_eq!(compiled.validate_data(&valid).unwrap(), []);
+            let errors = compiled.validate_data(&invalid).unwrap();
+            assert_eq!(errors.len(), 5, "{}: {:?}", validator, errors);
+            let error = errors
+                .iter()
+                .find(|error| error.instance_path == "/name")
+                .unwrap();
+            assert_eq!(
+                error.schema_path, "/properties/name/format",
+                "{}",
+                validator
+            );
+            assert_eq!(error.keyword, "format");
+            assert_eq!(error.message, "\"abc\" is not a 'upper'");
+            assert!(compiled
+                .validate_data(&json!({ "timeout": "P1DT2H" }))
+                .unwrap()
+                .is_empty());
+        }
+    }
+
+    #[test]
+    fn formats_file() {
+        let path = std::env::temp_dir().join(format!("jsonst-formats-{}.json", std::process::id()));
+        std::fs::write(&path, r#"{ "upper": "^[A-Z]+$", "broken": "^[A-Z" }"#).unwrap();
+        let mut formats = Formats::builtin(FormatMode::Assertion);
+        formats.load_file(&path).unwrap();
+        assert!(formats.patterns.contains_key("upper"));
+        assert!(!formats.patterns.contains_key("broken"));
+
+        std::fs::write(&path, r#"["^[A-Z]+$"]"#).unwrap();
+        let error = formats.load_file(&path).unwrap_err();
+        assert_eq!(error.code(), "parse");
+        std::fs::write(&path, r#"{ "upper": "#).unwrap();
+        let error = formats.load_file(&path).unwrap_err();
+        assert_eq!(error.code(), "parse");
+        std::fs::remove_file(&path).unwrap();
+        let error = Formats::new(FormatMode::Assertion, &[path]).err().unwrap();
+        assert_eq!(error.code(), "open");
+    }
+
+    #[test]
+    fn format_annotations() {
+        let formats = Formats::builtin(FormatMode::Annotation);
+        let schema = formats.apply(&json!({
+            "items": [{ "format": "email" }, { "format": "semver" }]
+        }));
+        assert_eq!(schema, json!({ "items": [{}, {}] }));
+        for validator in Validator::ALL {
+            let compiled = validator.compile_schema(&schema, None).unwrap();
+            assert!(compiled
+                .validate_data(&json!(["nobody", "1.0"]))
+                .unwrap()
+                .is_empty());
+        }
+    }
+}
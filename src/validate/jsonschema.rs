@@ -4,48 +4,61 @@
 
 */
 
-use super::{Error, Path, Result, Standard};
+use super::{Error, Result, Standard, ValidationError};
 use jsonschema::{Draft, JSONSchema, CompilationOptions};
 
+/// Supported standards
+pub const STANDARDS: &[Standard] = &[
+    Standard::Draft4,
+    Standard::Draft6,
+    Standard::Draft7,
+    Standard::Draft201909,
+    Standard::Draft202012,
+];
+
 pub struct CompiledSchema<'c> {
     schema: JSONSchema,
-    _phantom: core::marker::PhantomData<&'c char>,
+    source: &'c json::Value,
 }
 
 impl<'c> CompiledSchema<'c> {
     pub fn compile(schema: &'c json::Value, std: Option<Standard>) -> Result<Self> {
         let mut opts = CompilationOptions::default();
+        // formats is removed from schema when it should be annotations
+        opts.should_validate_formats(true);
 
         if let Some(std) = std {
             opts.with_draft(conv_std(std));
         }
 
         opts.compile(schema)
-            .map_err(|error| {
-                log::error!("Unable to compile JSON Schema due to: {}", error);
-                Error::Compile
-            })
-            .map(|schema| Self { schema, _phantom: core::marker::PhantomData })
+            .map_err(|error| Error::Compile(format!("Unable to compile JSON Schema due to: {}", error)))
+            .map(|compiled| Self { schema: compiled, source: schema })
+    }
+
+    pub fn source(&self) -> &'c json::Value {
+        self.source
     }
 
-    pub fn validate(&self, path: &Path, data: &json::Value, quiet: bool) -> Result<u32> {
-        Ok(if quiet {
-            u32::from(!self.schema.is_valid(data))
-        } else if let Err(errors) = self.schema.validate(data) {
-            println!("Data is not valid");
-            #[allow(clippy::suspicious_map)]
-            {
-                errors
-                    .map(|error| {
-                        println!("{}: {}", path.display(), error);
-                    })
-                    .count() as u32
-            }
+    pub fn validate(&self, data: &json::Value) -> Result<Vec<ValidationError>> {
+        Ok(if let Err(errors) = self.schema.validate(data) {
+            errors
+                .map(|error| {
+                    ValidationError::new(
+                        error.instance_path.to_string(),
+                        error.schema_path.to_string(),
+                        error.to_string(),
+                    )
+                })
+                .collect()
         } else {
-            println!("Data is valid");
-            0
+            Vec::new()
         })
     }
+
+    pub fn is_valid(&self, data: &json::Value) -> Result<bool> {
+        Ok(self.schema.is_valid(data))
+    }
 }
 
 fn conv_std(std: Standard) -> Draft {
@@ -53,5 +66,7 @@ fn conv_std(std: Standard) -> Draft {
         Standard::Draft4 => Draft::Draft4,
         Standard::Draft6 => Draft::Draft6,
         Standard::Draft7 => Draft::Draft7,
+        Standard::Draft201909 => Draft::Draft201909,
+        Standard::Draft202012 => Draft::Draft202012,
     }
 }
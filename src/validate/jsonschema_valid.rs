@@ -4,51 +4,74 @@
 
 */
 
-use super::{Error, Path, Result, Standard};
+use super::{
+    to_pointer, unsupported_standard, Error, Result, Standard, ValidationError, Validator,
+};
 use jsonschema_valid::{schemas::Draft, Config};
 
+/// Supported standards
+pub const STANDARDS: &[Standard] = &[Standard::Draft4, Standard::Draft6, Standard::Draft7];
+
 pub struct CompiledSchema<'c> {
     schema: Config<'c>,
+    source: &'c json::Value,
 }
 
 impl<'c> CompiledSchema<'c> {
     pub fn compile(schema: &'c json::Value, std: Option<Standard>) -> Result<Self> {
-        Config::from_schema(schema, std.map(conv_std))
+        let std = std.map(conv_std).transpose()?;
+        Config::from_schema(schema, std)
             .map_err(|error| {
-                log::error!("Unable to compile JSON Schema due to: {}", error);
-                Error::Compile
+                Error::Compile(format!("Unable to compile JSON Schema due to: {}", error))
+            })
+            .map(|compiled| Self {
+                schema: compiled,
+                source: schema,
             })
-            .map(|schema| Self { schema })
     }
 
-    pub fn validate(&self, path: &Path, data: &json::Value, quiet: bool) -> Result<u32> {
+    pub fn source(&self) -> &'c json::Value {
+        self.source
+    }
+
+    pub fn validate(&self, data: &json::Value) -> Result<Vec<ValidationError>> {
         Ok(if let Err(errors) = self.schema.validate(data) {
-            if quiet {
-                1
-            } else {
-                println!("Data is not valid");
-                #[allow(clippy::suspicious_map)]
-                {
-                    errors
-                        .map(|error| {
-                            println!("{}: {}", path.display(), error);
-                        })
-                        .count() as u32
-                }
-            }
+            errors
+                .map(|error| {
+                    // paths are stored in reverse order
+                    let result = ValidationError::new(
+                        to_pointer(error.instance_path.iter().rev()),
+                        to_pointer(error.schema_path.iter().rev()),
+                        &error.msg,
+                    );
+                    // use documentation of failed schema node as details
+                    if let Some(description) = error
+                        .schema
+                        .as_ref()
+                        .and_then(|schema| schema.get("description"))
+                        .and_then(|description| description.as_str())
+                    {
+                        result.with_detail(description)
+                    } else {
+                        result
+                    }
+                })
+                .collect()
         } else {
-            if !quiet {
-                println!("Data is valid");
-            }
-            0
+            Vec::new()
         })
     }
+
+    pub fn is_valid(&self, data: &json::Value) -> Result<bool> {
+        Ok(self.schema.validate(data).is_ok())
+    }
 }
 
-fn conv_std(std: Standard) -> Draft {
-    match std {
+fn conv_std(std: Standard) -> Result<Draft> {
+    Ok(match std {
         Standard::Draft4 => Draft::Draft4,
         Standard::Draft6 => Draft::Draft6,
         Standard::Draft7 => Draft::Draft7,
-    }
+        _ => return Err(unsupported_standard(Validator::JsonSchemaValid, std)),
+    })
 }
@@ -0,0 +1,285 @@
+/*!
+
+Bundled meta-schemas and checking schemas against it
+
+*/
+
+use super::Standard;
+use crate::parse::push_segment;
+use json::{Map, Value};
+
+macro_rules! meta_schema {
+    ($path:literal) => {
+        include_str!(concat!("meta_schemas/", $path))
+    };
+}
+
+/// Vocabulary meta-schemas of draft 2019-09
+const DRAFT201909_VOCABS: &[&str] = &[
+    meta_schema!("draft2019-09/meta/core.json"),
+    meta_schema!("draft2019-09/meta/applicator.json"),
+    meta_schema!("draft2019-09/meta/validation.json"),
+    meta_schema!("draft2019-09/meta/meta-data.json"),
+    meta_schema!("draft2019-09/meta/format.json"),
+    meta_schema!("draft2019-09/meta/content.json"),
+];
+
+/// Vocabulary meta-schemas of draft 2020-12
+const DRAFT202012_VOCABS: &[&str] = &[
+    meta_schema!("draft2020-12/meta/core.json"),
+    meta_schema!("draft2020-12/meta/applicator.json"),
+    meta_schema!("draft2020-12/meta/unevaluated.json"),
+    meta_schema!("draft2020-12/meta/validation.json"),
+    meta_schema!("draft2020-12/meta/meta-data.json"),
+    meta_schema!("draft2020-12/meta/format-annotation.json"),
+    meta_schema!("draft2020-12/meta/content.json"),
+];
+
+/// Get bundled meta-schema of standard
+///
+/// Vocabularies are embedded into `$defs` so no fetching is needed.
+pub fn meta_schema(std: Standard) -> Value {
+    let (source, vocabs) = match std {
+        Standard::Draft4 => (meta_schema!("draft4.json"), &[][..]),
+        Standard::Draft6 => (meta_schema!("draft6.json"), &[][..]),
+        Standard::Draft7 => (meta_schema!("draft7.json"), &[][..]),
+        Standard::Draft201909 => (meta_schema!("draft2019-09/schema.json"), DRAFT201909_VOCABS),
+        Standard::Draft202012 => (meta_schema!("draft2020-12/schema.json"), DRAFT202012_VOCABS),
+    };
+    let mut schema: Value = json::from_str(source).expect("Valid bundled meta-schema");
+    if !vocabs.is_empty() {
+        if let Some(object) = schema.as_object_mut() {
+            let defs = vocabs
+                .iter()
+                .enumerate()
+                .map(|(index, source)| {
+                    (
+                        format!("vocab{}", index),
+                        json::from_str(source).expect("Valid bundled meta-schema"),
+                    )
+                })
+                .collect::<Map<_, _>>();
+            object.insert("$defs".into(), Value::Object(defs));
+        }
+        let id = schema["$id"].clone();
+        make_refs_static(&mut schema, &id);
+    }
+    schema
+}
+
+/// Replace dynamic references by static references to root meta-schema
+///
+/// Not all validators supports `$recursiveRef` and `$dynamicRef` but meta-schema
+/// is never extended here so it always refers to root.
+fn make_refs_static(schema: &mut Value, root: &Value) {
+    match schema {
+        Value::Object(object) => {
+            for key in ["$recursiveRef", "$dynamicRef"] {
+                // keywords definitions in core vocabulary are objects
+                if object.get(key).map(Value::is_string) == Some(true) {
+                    object.remove(key);
+                    object.insert("$ref".into(), root.clone());
+                }
+            }
+            for value in object.values_mut() {
+                make_refs_static(value, root);
+            }
+        }
+        Value::Array(array) => {
+            for value in array {
+                make_refs_static(value, root);
+            }
+        }
+        _ => (),
+    }
+}
+
+/// Check that keyword is defined by standard
+fn is_known_keyword(std: Standard, keyword: &str) -> bool {
+    const DRAFT4: &[&str] = &[
+        "$schema",
+        "$ref",
+        "title",
+        "description",
+        "default",
+        "type",
+        "enum",
+        "multipleOf",
+        "maximum",
+        "exclusiveMaximum",
+        "minimum",
+        "exclusiveMinimum",
+        "maxLength",
+        "minLength",
+        "pattern",
+        "items",
+        "additionalItems",
+        "maxItems",
+        "minItems",
+        "uniqueItems",
+        "maxProperties",
+        "minProperties",
+        "required",
+        "properties",
+        "patternProperties",
+        "additionalProperties",
+        "dependencies",
+        "allOf",
+        "anyOf",
+        "oneOf",
+        "not",
+        "definitions",
+        "format",
+    ];
+    const DRAFT6: &[&str] = &["$id", "const", "contains", "propertyNames", "examples"];
+    const DRAFT7: &[&str] = &[
+        "$comment",
+        "if",
+        "then",
+        "else",
+        "readOnly",
+        "writeOnly",
+        "contentMediaType",
+        "contentEncoding",
+    ];
+    const DRAFT201909: &[&str] = &[
+        "$anchor",
+        "$defs",
+        "$recursiveRef",
+        "$recursiveAnchor",
+        "$vocabulary",
+        "dependentRequired",
+        "dependentSchemas",
+        "maxContains",
+        "minContains",
+        "unevaluatedItems",
+        "unevaluatedProperties",
+        "deprecated",
+        "contentSchema",
+    ];
+    const DRAFT202012: &[&str] = &["prefixItems", "$dynamicRef", "$dynamicAnchor"];
+
+    let lists: &[&[&str]] = match std {
+        Standard::Draft4 => &[DRAFT4, &["id"]],
+        Standard::Draft6 => &[DRAFT4, DRAFT6],
+        Standard::Draft7 => &[DRAFT4, DRAFT6, DRAFT7],
+        Standard::Draft201909 => &[DRAFT4, DRAFT6, DRAFT7, DRAFT201909],
+        Standard::Draft202012 => &[DRAFT4, DRAFT6, DRAFT7, DRAFT201909, DRAFT202012],
+    };
+    lists.iter().any(|list| list.contains(&keyword))
+}
+
+/// Find keywords which isn't defined by standard
+///
+/// Unknown keywords are allowed but usually it is typos.
+/// Returns sorted schema pointers of found keywords.
+pub fn unknown_keywords(schema: &Value, std: Standard) -> Vec<String> {
+    let mut found = Vec::new();
+    find_unknown(schema, std, String::new(), &mut found);
+    // order of keys depends on enabled features of serde_json
+    found.sort();
+    found
+}
+
+fn find_unknown(schema: &Value, std: Standard, pointer: String, found: &mut Vec<String>) {
+    let object = if let Value::Object(object) = schema {
+        object
+    } else {
+        return;
+    };
+    for (key, value) in object {
+        let pointer = push_segment(&pointer, key);
+        if !is_known_keyword(std, key) {
+            found.push(pointer);
+            continue;
+        }
+        match (key.as_str(), value) {
+            (
+                "properties" | "patternProperties" | "definitions" | "$defs" | "dependentSchemas"
+                | "dependencies",
+                Value::Object(schemas),
+            ) => {
+                for (name, schema) in schemas {
+                    find_unknown(schema, std, push_segment(&pointer, name), found);
+                }
+            }
+            ("items" | "prefixItems" | "allOf" | "anyOf" | "oneOf", Value::Array(schemas)) => {
+                for (index, schema) in schemas.iter().enumerate() {
+                    find_unknown(
+                        schema,
+                        std,
+                        push_segment(&pointer, &index.to_string()),
+                        found,
+                    );
+                }
+            }
+            (
+                "items"
+                | "additionalItems"
+                | "additionalProperties"
+                | "not"
+                | "if"
+                | "then"
+                | "else"
+                | "contains"
+                | "propertyNames"
+                | "unevaluatedItems"
+                | "unevaluatedProperties"
+                | "contentSchema",
+                _,
+            ) => find_unknown(value, std, pointer, found),
+            _ => (),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn bundled_meta_schemas() {
+        for name in Standard::LIST {
+            let schema = meta_schema(name.parse().unwrap());
+            assert!(schema.get("$schema").is_some());
+        }
+        assert_eq!(
+            meta_schema(Standard::Draft202012)["$defs"]
+                .as_object()
+                .unwrap()
+                .len(),
+            7
+        );
+    }
+
+    #[test]
+    fn find_unknown_keywords() {
+        let schema = json::json!({
+            "type": "object",
+            "requred": ["a"],
+            "properties": {
+                "requred": { "type": "string", "minLenght": 1 },
+                "b": { "items": [{ "typ": "string" }] }
+            },
+            "dependencies": { "a": ["b"] },
+            "$defs": { "c": { "const": 1 } }
+        });
+        assert_eq!(
+            unknown_keywords(&schema, Standard::Draft7),
+            [
+                "/$defs",
+                "/properties/b/items/0/typ",
+                "/properties/requred/minLenght",
+                "/requred"
+            ]
+        );
+        assert_eq!(
+            unknown_keywords(&schema, Standard::Draft202012),
+            [
+                "/properties/b/items/0/typ",
+                "/properties/requred/minLenght",
+                "/requred"
+            ]
+        );
+    }
+}
@@ -0,0 +1,415 @@
+/*!
+
+Standardized JSON Schema output formats (flag, basic, detailed, verbose)
+
+*/
+
+use super::{Locator, ValidationError};
+use crate::SourceMap;
+use json::{json, Map, Value};
+
+/// Validation output format
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum OutputFormat {
+    /// Human-readable text
+    Text,
+    /// Only validation result
+    Flag,
+    /// Flat list of errors
+    Basic,
+    /// Condensed hierarchy of errors
+    Detailed,
+    /// Full hierarchy of errors
+    ///
+    /// Unlike the specification it has no nodes of valid subschemas because
+    /// validators reports only failed keywords.
+    Verbose,
+}
+
+impl std::str::FromStr for OutputFormat {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        Ok(match s {
+            "text" => Self::Text,
+            "flag" => Self::Flag,
+            "basic" => Self::Basic,
+            "detailed" => Self::Detailed,
+            "verbose" => Self::Verbose,
+            _ => return Err("unknown"),
+        })
+    }
+}
+
+impl std::fmt::Display for OutputFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::Text => "text",
+            Self::Flag => "flag",
+            Self::Basic => "basic",
+            Self::Detailed => "detailed",
+            Self::Verbose => "verbose",
+        }
+        .fmt(f)
+    }
+}
+
+impl OutputFormat {
+    /// List of all variants
+    pub const LIST: &'static [&'static str] = &["text", "flag", "basic", "detailed", "verbose"];
+
+    /// Build output structure from the list of errors
+    ///
+    /// Returns `None` for text format which isn't structured. Absolute keyword
+    /// locations is added when schema locator has base URI. Units of errors gets
+    /// `line` and `column` of instance when source map is given.
+    pub fn build(
+        &self,
+        locator: Option<&Locator>,
+        source_map: Option<&SourceMap>,
+        errors: &[ValidationError],
+    ) -> Option<Value> {
+        let context = Context {
+            locator,
+            source_map,
+        };
+        Some(match self {
+            Self::Text => return None,
+            Self::Flag => json!({ "valid": errors.is_empty() }),
+            Self::Basic => {
+                let mut root = Map::new();
+                root.insert("valid".into(), errors.is_empty().into());
+                if !errors.is_empty() {
+                    root.insert(
+                        "errors".into(),
+                        errors
+                            .iter()
+                            .map(|error| output_unit(error, &context))
+                            .collect(),
+                    );
+                }
+                root.into()
+            }
+            Self::Detailed | Self::Verbose => {
+                let mut tree = Node::default();
+                for error in errors {
+                    tree.insert(error);
+                }
+                if matches!(self, Self::Detailed) {
+                    tree.condense();
+                }
+                tree.to_json(&context, "")
+            }
+        })
+    }
+}
+
+/// Sources of locations which is added to output units
+struct Context<'a, 's> {
+    locator: Option<&'a Locator<'s>>,
+    source_map: Option<&'a SourceMap>,
+}
+
+impl Context<'_, '_> {
+    /// Add absolute location of keyword through references
+    fn add_absolute_location(&self, unit: &mut Map<String, Value>, keyword_location: &str) {
+        if let Some(uri) = self
+            .locator
+            .and_then(|locator| locator.locate(keyword_location))
+            .and_then(|location| location.uri())
+        {
+            unit.insert("absoluteKeywordLocation".into(), uri.to_string().into());
+        }
+    }
+
+    /// Add line and column of instance in source
+    fn add_position(&self, unit: &mut Map<String, Value>, instance_location: &str) {
+        if let Some(location) = self
+            .source_map
+            .and_then(|source_map| source_map.find(instance_location))
+        {
+            unit.insert("line".into(), location.line.into());
+            unit.insert("column".into(), location.column.into());
+        }
+    }
+}
+
+fn output_unit(error: &ValidationError, context: &Context) -> Value {
+    let mut unit = Map::new();
+    unit.insert("keywordLocation".into(), error.schema_path.clone().into());
+    context.add_absolute_location(&mut unit, &error.schema_path);
+    unit.insert(
+        "instanceLocation".into(),
+        error.instance_path.clone().into(),
+    );
+    context.add_position(&mut unit, &error.instance_path);
+    unit.insert("error".into(), error.message.clone().into());
+    unit.into()
+}
+
+/// Tree of errors arranged by keyword location
+#[derive(Default)]
+struct Node {
+    /// Path segments relative to parent node
+    segments: Vec<String>,
+    /// Errors which located exactly at this node
+    errors: Vec<(String, String)>,
+    /// Nested nodes
+    children: Vec<Node>,
+}
+
+impl Node {
+    fn insert(&mut self, error: &ValidationError) {
+        let mut node = self;
+        for segment in split_pointer(&error.schema_path) {
+            let index = if let Some(index) = node
+                .children
+                .iter()
+                .position(|child| child.segments[0] == segment)
+            {
+                index
+            } else {
+                node.children.push(Node {
+                    segments: vec![segment.into()],
+                    ..Default::default()
+                });
+                node.children.len() - 1
+            };
+            node = &mut node.children[index];
+        }
+        node.errors
+            .push((error.instance_path.clone(), error.message.clone()));
+    }
+
+    /// Collapse intermediate nodes which hasn't own errors and has single child
+    fn condense(&mut self) {
+        for child in &mut self.children {
+            child.condense();
+            while child.errors.is_empty() && child.children.len() == 1 {
+                let grandchild = child.children.pop().unwrap();
+                child.segments.extend(grandchild.segments);
+                child.errors = grandchild.errors;
+                child.children = grandchild.children;
+            }
+        }
+    }
+
+    fn instance_location(&self) -> String {
+        let mut locations = self
+            .errors
+            .iter()
+            .map(|(location, _)| location.clone())
+            .chain(self.children.iter().map(Node::instance_location));
+        let first = locations.next().unwrap_or_default();
+        locations.fold(first, |common, location| {
+            let common = split_pointer(&common)
+                .zip(split_pointer(&location))
+                .take_while(|(a, b)| a == b)
+                .map(|(a, _)| a)
+                .collect::<Vec<_>>();
+            common.iter().fold(String::new(), |mut pointer, segment| {
+                pointer.push('/');
+                pointer.push_str(segment);
+                pointer
+            })
+        })
+    }
+
+    fn to_json(&self, context: &Context, parent: &str) -> Value {
+        let mut keyword_location = parent.to_string();
+        for segment in &self.segments {
+            keyword_location.push('/');
+            keyword_location.push_str(segment);
+        }
+
+        let mut node = Map::new();
+        node.insert("valid".into(), false.into());
+        node.insert("keywordLocation".into(), keyword_location.clone().into());
+        context.add_absolute_location(&mut node, &keyword_location);
+
+        if self.children.is_empty() && self.errors.len() == 1 {
+            let (instance_location, error) = &self.errors[0];
+            node.insert("instanceLocation".into(), instance_location.clone().into());
+            context.add_position(&mut node, instance_location);
+            node.insert("error".into(), error.clone().into());
+        } else if self.errors.is_empty() && self.children.is_empty() {
+            // root node of valid data
+            node.insert("valid".into(), true.into());
+            node.insert("instanceLocation".into(), "".into());
+        } else {
+            node.insert("instanceLocation".into(), self.instance_location().into());
+            node.insert(
+                "errors".into(),
+                self.errors
+                    .iter()
+                    .map(|(instance_location, error)| {
+                        let mut unit = Map::new();
+                        unit.insert("valid".into(), false.into());
+                        unit.insert("keywordLocation".into(), keyword_location.clone().into());
+                        context.add_absolute_location(&mut unit, &keyword_location);
+                        unit.insert("instanceLocation".into(), instance_location.clone().into());
+                        context.add_position(&mut unit, instance_location);
+                        unit.insert("error".into(), error.clone().into());
+                        Value::from(unit)
+                    })
+                    .chain(
+                        self.children
+                            .iter()
+                            .map(|child| child.to_json(context, &keyword_location)),
+                    )
+                    .collect(),
+            );
+        }
+
+        node.into()
+    }
+}
+
+fn split_pointer(pointer: &str) -> impl Iterator<Item = &str> {
+    pointer.split('/').skip(1)
+}
+
+/// Convert path segments to JSON pointer
+#[cfg(feature = "jsonschema-valid")]
+pub fn to_pointer<I, S>(segments: I) -> String
+where
+    S: AsRef<str>,
+    I: IntoIterator<Item = S>,
+{
+    let mut pointer = String::new();
+    for segment in segments {
+        pointer.push('/');
+        for ch in segment.as_ref().chars() {
+            match ch {
+                '~' => pointer.push_str("~0"),
+                '/' => pointer.push_str("~1"),
+                _ => pointer.push(ch),
+            }
+        }
+    }
+    pointer
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn units() -> Vec<ValidationError> {
+        vec![
+            ValidationError::new(
+                "/1",
+                "/items/$ref/required",
+                "Required property 'foo' is missing",
+            ),
+            ValidationError::new(
+                "/1",
+                "/items/$ref/additionalProperties",
+                "Additional property 'bar' is not allowed",
+            ),
+        ]
+    }
+
+    #[test]
+    fn flag_output() {
+        assert_eq!(
+            OutputFormat::Flag.build(None, None, &[]),
+            Some(json!({ "valid": true }))
+        );
+        assert_eq!(
+            OutputFormat::Flag.build(None, None, &units()),
+            Some(json!({ "valid": false }))
+        );
+    }
+
+    #[test]
+    fn basic_output() {
+        let schema = json!({
+            "items": { "$ref": "common.json#/definitions/item" },
+            "definitions": {
+                "https___example.com_common.json": {
+                    "$id": "https://example.com/common.json",
+                    "definitions": {
+                        "item": { "required": ["foo"], "additionalProperties": false }
+                    }
+                }
+            }
+        });
+        let base: crate::Uri = "https://example.com/schema.json".parse().unwrap();
+        let locator = Locator::new(&schema, Some(&base));
+        let source_map = crate::Format::Json.source_map(b"[{},\n {\"bar\": 1}]");
+        assert_eq!(
+            OutputFormat::Basic.build(Some(&locator), source_map.as_ref(), &units()),
+            Some(json!({
+                "valid": false,
+                "errors": [
+                    {
+                        "keywordLocation": "/items/$ref/required",
+                        "absoluteKeywordLocation": "https://example.com/common.json#/definitions/item/required",
+                        "instanceLocation": "/1",
+                        "line": 2,
+                        "column": 2,
+                        "error": "Required property 'foo' is missing"
+                    },
+                    {
+                        "keywordLocation": "/items/$ref/additionalProperties",
+                        "absoluteKeywordLocation": "https://example.com/common.json#/definitions/item/additionalProperties",
+                        "instanceLocation": "/1",
+                        "line": 2,
+                        "column": 2,
+                        "error": "Additional property 'bar' is not allowed"
+                    }
+                ]
+            }))
+        );
+    }
+
+    #[test]
+    fn detailed_output() {
+        assert_eq!(
+            OutputFormat::Detailed.build(None, None, &units()),
+            Some(json!({
+                "valid": false,
+                "keywordLocation": "",
+                "instanceLocation": "/1",
+                "errors": [
+                    {
+                        "valid": false,
+                        "keywordLocation": "/items/$ref",
+                        "instanceLocation": "/1",
+                        "errors": [
+                            {
+                                "valid": false,
+                                "keywordLocation": "/items/$ref/required",
+                                "instanceLocation": "/1",
+                                "error": "Required property 'foo' is missing"
+                            },
+                            {
+                                "valid": false,
+                                "keywordLocation": "/items/$ref/additionalProperties",
+                                "instanceLocation": "/1",
+                                "error": "Additional property 'bar' is not allowed"
+                            }
+                        ]
+                    }
+                ]
+            }))
+        );
+    }
+
+    #[test]
+    fn verbose_output() {
+        let output = OutputFormat::Verbose.build(None, None, &units()).unwrap();
+        assert_eq!(output["errors"][0]["keywordLocation"], "/items");
+        assert_eq!(
+            output["errors"][0]["errors"][0]["keywordLocation"],
+            "/items/$ref"
+        );
+    }
+
+    #[cfg(feature = "jsonschema-valid")]
+    #[test]
+    fn pointer_escaping() {
+        assert_eq!(to_pointer(["a/b", "c~d", "0"]), "/a~1b/c~0d/0");
+        assert_eq!(to_pointer::<_, &str>([]), "");
+    }
+}
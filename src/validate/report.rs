@@ -0,0 +1,361 @@
+/*!
+
+Reporting validation results
+
+*/
+
+use super::{
+    best_match::{Explanation, Matcher},
+    Branches, Locator, OutputFormat, ValidationError,
+};
+use crate::{SourceMap, Uri};
+use json::Value;
+use std::{fmt::Write, path::Path};
+
+/// Validation results reporter
+pub struct Reporter<'a> {
+    format: OutputFormat,
+    locator: Option<Locator<'a>>,
+    matcher: Option<Matcher<'a>>,
+    schema_pointer: Option<&'a str>,
+    subject: &'static str,
+    verbose: bool,
+}
+
+impl<'a> Reporter<'a> {
+    /// Create new reporter
+    pub fn new(format: OutputFormat, verbose: bool) -> Self {
+        Self {
+            format,
+            locator: None,
+            matcher: None,
+            schema_pointer: None,
+            subject: "Data",
+            verbose,
+        }
+    }
+
+    /// Use validating schema with its base URI to build absolute keyword locations
+    pub fn with_schema(mut self, schema: &'a Value, base: Option<&Uri>) -> Self {
+        self.locator = Some(Locator::new(schema, base));
+        self
+    }
+
+    /// Explain failed `oneOf` and `anyOf` using its branches
+    ///
+    /// Failure is replaced by errors of best matching branch. Verbose reports
+    /// keeps failure followed by errors of all branches.
+    pub fn with_best_matches(mut self, branches: &'a Branches<'a>) -> Self {
+        self.matcher = Some(branches.matcher());
+        self
+    }
+
+    /// Report keyword locations relative to schema which is narrowed by pointer
+    ///
+    /// Schema given by `with_schema` should be the whole one.
+    pub fn with_schema_pointer(mut self, pointer: &'a str) -> Self {
+        self.schema_pointer = Some(pointer);
+        self
+    }
+
+    /// Name validated inputs in text report ("Data" by default)
+    pub fn with_subject(mut self, subject: &'static str) -> Self {
+        self.subject = subject;
+        self
+    }
+
+    /// Make report for validated input
+    ///
+    /// The source map is used to add line and column of values to report.
+    /// Structured output gets `input` member with path of input.
+    pub fn report(
+        &self,
+        path: &Path,
+        source_map: Option<&SourceMap>,
+        data: &Value,
+        errors: &[ValidationError],
+    ) -> String {
+        let mut explained = Vec::with_capacity(errors.len());
+        self.explain(data, errors, &mut explained);
+        if let Some(pointer) = self.schema_pointer {
+            for error in &mut explained {
+                error.schema_path.insert_str(0, pointer);
+            }
+        }
+        let errors = &explained;
+
+        if let Some(output) = self.format.build(self.locator.as_ref(), source_map, errors) {
+            report_structured(path, output)
+        } else {
+            self.report_text(path, source_map, errors)
+        }
+    }
+
+    /// Make flag report for input which is only checked to be valid
+    pub fn report_validity(&self, path: &Path, valid: bool) -> String {
+        report_structured(path, json::json!({ "valid": valid }))
+    }
+
+    /// Add errors explaining failures by its best matching branches
+    fn explain(
+        &self,
+        data: &Value,
+        errors: &[ValidationError],
+        explained: &mut Vec<ValidationError>,
+    ) {
+        let verbose = self.verbose || self.format == OutputFormat::Verbose;
+        for error in errors {
+            let Explanation { best, branches } = match self
+                .matcher
+                .as_ref()
+                .and_then(|matcher| matcher.explain(data, error))
+            {
+                Some(explanation) => explanation,
+                None => {
+                    explained.push(error.clone());
+                    continue;
+                }
+            };
+            if verbose {
+                let mut error = error.clone();
+                let best = format!("{} branch {} is the best match", error.keyword, best);
+                error.detail = Some(match error.detail.take() {
+                    Some(detail) => format!("{}\n{}", detail, best),
+                    None => best,
+                });
+                explained.push(error);
+                for errors in &branches {
+                    self.explain(data, errors, explained);
+                }
+            } else {
+                self.explain(data, &branches[best], explained);
+            }
+        }
+    }
+
+    fn report_text(
+        &self,
+        path: &Path,
+        source_map: Option<&SourceMap>,
+        errors: &[ValidationError],
+    ) -> String {
+        let mut report = String::new();
+
+        // every line starts with path so reports of parallel jobs can be told apart
+        if errors.is_empty() {
+            let _ = writeln!(report, "{}: {} is valid", path.display(), self.subject);
+        } else {
+            let _ = writeln!(report, "{}: {} is not valid", path.display(), self.subject);
+        }
+
+        for error in errors {
+            if let Some(location) =
+                source_map.and_then(|source_map| source_map.find(&error.instance_path))
+            {
+                let _ = writeln!(report, "{}:{}: {}", path.display(), location, error);
+            } else {
+                let _ = writeln!(report, "{}: {}", path.display(), error);
+            }
+            if self.verbose {
+                let _ = writeln!(
+                    report,
+                    "  keyword '{}' at schema path '{}'",
+                    error.keyword, error.schema_path
+                );
+                if let Some(detail) = &error.detail {
+                    for line in detail.lines() {
+                        let _ = writeln!(report, "  {}", line);
+                    }
+                }
+            }
+        }
+
+        report
+    }
+}
+
+/// Structured output always fits in single line so inputs is told apart by `input` member
+fn report_structured(path: &Path, mut output: Value) -> String {
+    if let Value::Object(output) = &mut output {
+        output.insert("input".into(), path.display().to_string().into());
+    }
+    let mut report = output.to_string();
+    report.push('\n');
+    report
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{Format, Validator};
+
+    #[test]
+    fn text_report() {
+        let errors = [
+            ValidationError::new("/a", "/properties/a/type", "Invalid type")
+                .with_detail("Expected integer"),
+        ];
+        let path = Path::new("data.yaml");
+
+        let reporter = Reporter::new(OutputFormat::Text, false);
+        assert_eq!(
+            reporter.report(path, None, &Value::Null, &[]),
+            "data.yaml: Data is valid\n"
+        );
+        assert_eq!(
+            reporter.report(path, None, &Value::Null, &errors),
+            "data.yaml: Data is not valid\ndata.yaml: #/a: Invalid type\n"
+        );
+
+        let source_map = Format::Json.source_map(b"{\"a\": \"x\"}");
+        assert_eq!(
+            reporter.report(path, source_map.as_ref(), &Value::Null, &errors),
+            "data.yaml: Data is not valid\ndata.yaml:1:7: #/a: Invalid type\n"
+        );
+
+        let reporter = Reporter::new(OutputFormat::Text, true);
+        assert_eq!(
+            reporter.report(path, None, &Value::Null, &errors),
+            "data.yaml: Data is not valid\ndata.yaml: #/a: Invalid type\n  keyword 'type' at schema path '/properties/a/type'\n  Expected integer\n"
+        );
+    }
+
+    #[test]
+    fn schema_report() {
+        let errors = [ValidationError::new(
+            "/type",
+            "/properties/type/anyOf",
+            "Invalid type",
+        )];
+        let reporter = Reporter::new(OutputFormat::Text, false).with_subject("Schema");
+        assert_eq!(
+            reporter.report(Path::new("schema.json"), None, &Value::Null, &errors),
+            "schema.json: Schema is not valid\nschema.json: #/type: Invalid type\n"
+        );
+    }
+
+    #[test]
+    fn best_match_report() {
+        let schema = json::json!({
+            "items": {
+                "anyOf": [
+                    { "type": "string" },
+                    { "properties": { "name": { "type": "string" } } }
+                ]
+            }
+        });
+        let data = json::json!([{ "name": 1 }]);
+        let validator: Validator = Validator::LIST[0].parse().unwrap();
+        let compiled = validator.compile_schema(&schema, None).unwrap();
+        let errors = compiled.validate_data(&data).unwrap();
+        assert_eq!(errors.len(), 1);
+        let branches = Branches::new(&compiled, None);
+        let path = Path::new("data.json");
+
+        let reporter = Reporter::new(OutputFormat::Text, false).with_best_matches(&branches);
+        let report = reporter.report(path, None, &data, &errors);
+        assert_eq!(report.lines().count(), 2, "{}", report);
+        assert!(report.contains("data.json: #/0/name: "), "{}", report);
+
+        let reporter = Reporter::new(OutputFormat::Text, true).with_best_matches(&branches);
+        let report = reporter.report(path, None, &data, &errors);
+        assert!(
+            report.contains("keyword 'anyOf' at schema path '/items/anyOf'"),
+            "{}",
+            report
+        );
+        assert!(
+            report.contains("anyOf branch 1 is the best match"),
+            "{}",
+            report
+        );
+        assert!(
+            report.contains("at schema path '/items/anyOf/0/type'"),
+            "{}",
+            report
+        );
+        assert!(
+            report.contains("at schema path '/items/anyOf/1/properties/name/type'"),
+            "{}",
+            report
+        );
+
+        let reporter = Reporter::new(OutputFormat::Verbose, false).with_best_matches(&branches);
+        let output: Value = json::from_str(&reporter.report(path, None, &data, &errors)).unwrap();
+        let any_of = &output["errors"][0]["errors"][0];
+        assert_eq!(any_of["keywordLocation"], "/items/anyOf");
+        assert_eq!(any_of["errors"].as_array().unwrap().len(), 3);
+    }
+
+    #[test]
+    fn narrowed_schema_report() {
+        let schema = json::json!({
+            "definitions": {
+                "item": { "properties": { "id": { "$ref": "#/definitions/id" } } },
+                "id": { "type": "integer" }
+            }
+        });
+        let base: Uri = "https://example.com/schema.json".parse().unwrap();
+        let errors = [ValidationError::new(
+            "/id",
+            "/properties/id/$ref/type",
+            "Invalid type",
+        )];
+        let reporter = Reporter::new(OutputFormat::Basic, false)
+            .with_schema(&schema, Some(&base))
+            .with_schema_pointer("/definitions/item");
+        let output: Value =
+            json::from_str(&reporter.report(Path::new("data.json"), None, &Value::Null, &errors))
+                .unwrap();
+        assert_eq!(
+            output["errors"][0]["keywordLocation"],
+            "/definitions/item/properties/id/$ref/type"
+        );
+        assert_eq!(
+            output["errors"][0]["absoluteKeywordLocation"],
+            "https://example.com/schema.json#/definitions/id/type"
+        );
+    }
+
+    #[test]
+    fn structured_report() {
+        let reporter = Reporter::new(OutputFormat::Flag, true);
+        let report = reporter.report(Path::new("data.yaml"), None, &Value::Null, &[]);
+        assert!(report.ends_with('\n') && report.lines().count() == 1);
+        assert_eq!(
+            json::from_str::<Value>(&report).unwrap(),
+            json::json!({ "valid": true, "input": "data.yaml" })
+        );
+        let report = reporter.report_validity(Path::new("data.jsonl#1"), false);
+        assert_eq!(
+            json::from_str::<Value>(&report).unwrap(),
+            json::json!({ "valid": false, "input": "data.jsonl#1" })
+        );
+
+        let errors = [ValidationError::new(
+            "/a",
+            "/properties/a/type",
+            "Invalid type",
+        )];
+        for format in [
+            OutputFormat::Basic,
+            OutputFormat::Detailed,
+            OutputFormat::Verbose,
+        ] {
+            let reporter = Reporter::new(format, false);
+            let report = [("a.json", &errors[..]), ("b.json", &[])]
+                .iter()
+                .map(|(path, errors)| reporter.report(Path::new(path), None, &Value::Null, errors))
+                .collect::<String>();
+            let outputs = report
+                .lines()
+                .map(|line| json::from_str::<Value>(line).unwrap())
+                .collect::<Vec<_>>();
+            assert_eq!(outputs.len(), 2, "{}", format);
+            assert_eq!(outputs[0]["input"], "a.json", "{}", format);
+            assert_eq!(outputs[0]["valid"], false, "{}", format);
+            assert_eq!(outputs[1]["input"], "b.json", "{}", format);
+            assert_eq!(outputs[1]["valid"], true, "{}", format);
+        }
+    }
+}
@@ -0,0 +1,1022 @@
+/*!
+
+Resolving external references of JSON Schemas
+
+*/
+
+use super::Standard;
+use crate::{parse::push_segment, utils, Cache, Error, Format, Result, Uri};
+use json::{Map, Value};
+use percent_encoding::percent_decode_str;
+use std::{
+    collections::{HashMap, HashSet},
+    path::{Path, PathBuf},
+    sync::OnceLock,
+};
+
+#[cfg(feature = "http_req")]
+use crate::http::get_cached_with;
+
+/// Definition key of root schema embedded into narrowed schema
+const ROOT_KEY: &str = "jsonst-root";
+
+/// Keywords which values isn't subschemas
+pub(super) const DATA_KEYWORDS: &[&str] = &["enum", "const", "default", "examples"];
+
+/// Maximum number of references to follow in a row
+const REFS_LIMIT: usize = 16;
+
+/// Update interval of remote schemas
+#[cfg(feature = "http_req")]
+const UPDATE_INTERVAL: std::time::Duration = std::time::Duration::from_secs(24 * 60 * 60); // one day
+
+/// External references resolver
+pub struct Resolver {
+    /// Local schemas indexed by its ids
+    registry: HashMap<Uri, Value>,
+
+    #[cfg(feature = "http_req")]
+    cache: Cache,
+}
+
+impl Resolver {
+    /// Create new resolver
+    ///
+    /// Cache is used for remote schemas.
+    #[cfg_attr(not(feature = "http_req"), allow(unused_variables))]
+    pub fn new(cache: Cache) -> Self {
+        Self {
+            registry: HashMap::new(),
+
+            #[cfg(feature = "http_req")]
+            cache,
+        }
+    }
+
+    /// Index schemas from directory (recursively) by its ids
+    ///
+    /// Symbolic links are followed but each directory is visited only once.
+    pub fn load_dir(&mut self, dir: &Path) -> Result<()> {
+        let entries = std::fs::read_dir(dir).map_err(|source| Error::Read {
+            topic: "schema directory".into(),
+            path: dir.into(),
+            source,
+        })?;
+        let mut visited = HashSet::new();
+        visited.extend(dir.canonicalize().ok());
+        self.load_entries(entries, &mut visited);
+        Ok(())
+    }
+
+    fn load_entries(&mut self, entries: std::fs::ReadDir, visited: &mut HashSet<PathBuf>) {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                if !path.canonicalize().is_ok_and(|real| visited.insert(real)) {
+                    log::debug!("Skip already visited directory '{}'", path.display());
+                    continue;
+                }
+                match std::fs::read_dir(&path) {
+                    Ok(entries) => self.load_entries(entries, visited),
+                    Err(error) => log::warn!(
+                        "Unable to read schema directory '{}' due to: {}",
+                        path.display(),
+                        error
+                    ),
+                }
+            } else if let Some(format) = Format::from_path(&path) {
+                let topic = "JSON Schema";
+                let schema = utils::open_file(topic, &path)
+                    .and_then(|mut file| utils::read_input(topic, &path, &mut file))
+                    .and_then(|data| format.parse_data(topic, &path, &data))
+                    .map_err(|error| log::warn!("{}", error))
+                    .ok();
+                let id = schema.as_ref().and_then(|schema| {
+                    schema_id(schema, "$id").or_else(|| schema_id(schema, "id"))
+                });
+                if let (Some(schema), Some(id)) = (schema, id) {
+                    log::debug!("Found schema '{}' in '{}'", id, path.display());
+                    let id = without_fragment(&id);
+                    if self.registry.contains_key(&id) {
+                        log::warn!(
+                            "Schema '{}' from '{}' overrides previously found one",
+                            id,
+                            path.display()
+                        );
+                    }
+                    self.registry.insert(id, schema);
+                } else {
+                    log::debug!("Skip '{}' which isn't a schema with id", path.display());
+                }
+            }
+        }
+    }
+
+    /// Fetch external document
+    ///
+    /// Schemas from registry takes precedence. Local files are read directly
+    /// while remote documents are cached.
+    pub fn fetch(&self, url: &Uri) -> Result<Value> {
+        if let Some(schema) = self.registry.get(url) {
+            return Ok(schema.clone());
+        }
+        match url.scheme() {
+            #[cfg(feature = "http_req")]
+            "http" | "https" => {
+                get_cached_with(
+                    &self.cache,
+                    url.as_str(),
+                    UPDATE_INTERVAL,
+                    |media_type, body| {
+                        // media type takes precedence over extension
+                        let format = media_type
+                            .and_then(Format::from_media_type)
+                            .or_else(|| Format::from_path(Path::new(url.path())))
+                            .unwrap_or(Format::Json);
+                        format
+                            .parse_data("referenced JSON Schema", Path::new(url.as_str()), body)
+                            .map_err(Error::into_schema)
+                    },
+                )
+            }
+            "file" => fetch_file(url),
+            scheme => Err(Error::fetch(
+                url.as_str(),
+                format!("unsupported scheme '{}'", scheme),
+            )),
+        }
+    }
+
+    /// Bundle schema with all external documents which it references
+    ///
+    /// External documents are embedded into definitions of root schema as
+    /// resources with `$id` so any validator can resolve it without fetching.
+    /// The base URI is used to resolve relative references when schema has no id.
+    pub fn bundle(&self, mut schema: Value, base: Option<&Uri>) -> Value {
+        let std = Standard::detect(&schema);
+        let id_key = if std == Some(Standard::Draft4) {
+            "id"
+        } else {
+            "$id"
+        };
+        let root = schema_id(&schema, id_key).or_else(|| base.cloned());
+
+        let mut seen = HashSet::new();
+        if let Some(root) = &root {
+            seen.insert(without_fragment(root));
+        }
+
+        let mut queue = Vec::new();
+        collect_refs(&schema, id_key, root.as_ref(), &mut queue, &mut seen);
+
+        let mut resources = Vec::new();
+        let mut redirects = HashMap::new();
+        while let Some(url) = queue.pop() {
+            if !seen.insert(url.clone()) || url.host_str() == Some("json-schema.org") {
+                // already seen or well-known meta-schema which validators has
+                continue;
+            }
+            log::info!("Resolving external reference '{}'", url);
+            match self.fetch(&url) {
+                Ok(mut resource) => {
+                    collect_refs(&resource, id_key, Some(&url), &mut queue, &mut seen);
+                    let object = if let Some(object) = resource.as_object_mut() {
+                        object
+                    } else {
+                        continue;
+                    };
+                    let id = object
+                        .get(id_key)
+                        .and_then(|id| id.as_str())
+                        .and_then(|id| join(Some(&url), id))
+                        .map(|id| without_fragment(&id));
+                    match id {
+                        Some(id) if id != url => {
+                            // resource keeps own id so references to its location is redirected
+                            object.insert(id_key.into(), id.to_string().into());
+                            redirects.insert(url, id.clone());
+                            resources.push((id, resource));
+                        }
+                        Some(_) => resources.push((url, resource)),
+                        None => {
+                            // identify resource by its location
+                            object.insert(id_key.into(), url.to_string().into());
+                            resources.push((url, resource));
+                        }
+                    }
+                }
+                Err(error) => {
+                    log::warn!(
+                        "Unable to resolve external reference '{}' due to: {}",
+                        url,
+                        error
+                    );
+                }
+            }
+        }
+
+        if resources.is_empty() {
+            return schema;
+        }
+        if !redirects.is_empty() {
+            redirect_refs(&mut schema, id_key, root.as_ref(), &redirects);
+            for (_, resource) in &mut resources {
+                redirect_refs(resource, id_key, None, &redirects);
+            }
+        }
+
+        if let Some(object) = schema.as_object_mut() {
+            if let Some(root) = &root {
+                if !object.contains_key(id_key) {
+                    object.insert(id_key.into(), root.to_string().into());
+                }
+            }
+
+            let defs_key = match std {
+                Some(Standard::Draft201909 | Standard::Draft202012) => "$defs",
+                _ => "definitions",
+            };
+            if let Value::Object(defs) = object
+                .entry(defs_key)
+                .or_insert_with(|| Value::Object(Map::new()))
+            {
+                for (url, resource) in resources {
+                    defs.insert(resource_key(&url), resource);
+                }
+            }
+        }
+
+        schema
+    }
+
+    /// Get paths of local files which is embedded into bundled schema
+    pub fn bundled_files(schema: &Value) -> Vec<std::path::PathBuf> {
+        ["definitions", "$defs"]
+            .iter()
+            .filter_map(|key| schema.get(key)?.as_object())
+            .flat_map(|defs| defs.values())
+            .filter_map(|resource| {
+                let id = resource.get("$id").or_else(|| resource.get("id"))?;
+                let url = id.as_str()?.parse::<Uri>().ok()?;
+                if url.scheme() == "file" {
+                    url.to_file_path().ok()
+                } else {
+                    None
+                }
+            })
+            .collect()
+    }
+
+    /// Make standalone schema from subschema of root schema
+    ///
+    /// Subschema is located by schema path following references. Root schema is
+    /// embedded into definitions and local references which points outside of
+    /// definitions is rewritten to it so it still resolvable. Local references of
+    /// subschema within embedded resource is made absolute instead.
+    pub fn narrow(root: &Value, base: Option<&Uri>, schema_path: &str) -> Option<Value> {
+        let locator = Locator::new(root, base);
+        let location = locator.locate(schema_path)?;
+        let std = Standard::detect(root);
+        let (id_key, defs_key) = match std {
+            Some(Standard::Draft4) => ("id", "definitions"),
+            Some(Standard::Draft201909 | Standard::Draft202012) => ("$id", "$defs"),
+            _ => ("$id", "definitions"),
+        };
+
+        let mut subschema = location.schema.clone();
+        if std::ptr::eq(location.resource, root) {
+            rebase_refs(&mut subschema, defs_key, true);
+        } else if let (Some(base), false) = (&location.base, location.pointer.is_empty()) {
+            rewrite_local_refs(&mut subschema, true, &|reference| {
+                Some(format!("{}{}", base, reference))
+            });
+        }
+
+        let mut root = root.clone();
+        rebase_refs(&mut root, defs_key, true);
+        let mut root = match root {
+            Value::Object(root) => root,
+            _ => return Some(subschema),
+        };
+
+        let mut schema = Map::new();
+        for key in ["$schema", id_key, "definitions", "$defs"] {
+            if let Some(value) = root.remove(key) {
+                schema.insert(key.into(), value);
+            }
+        }
+        if let Value::Object(defs) = schema
+            .entry(defs_key)
+            .or_insert_with(|| Value::Object(Map::new()))
+        {
+            defs.insert(ROOT_KEY.into(), Value::Object(root));
+        }
+
+        match subschema {
+            Value::Object(subschema) => {
+                for (key, value) in subschema {
+                    match (schema.get_mut(&key), value) {
+                        (Some(Value::Object(defs)), Value::Object(own))
+                            if key == "definitions" || key == "$defs" =>
+                        {
+                            for (name, value) in own {
+                                defs.entry(name).or_insert(value);
+                            }
+                        }
+                        (_, value) => {
+                            schema.insert(key, value);
+                        }
+                    }
+                }
+            }
+            Value::Bool(false) => {
+                schema.insert("not".into(), Value::Object(Map::new()));
+            }
+            _ => (),
+        }
+
+        Some(Value::Object(schema))
+    }
+}
+
+/// Subschema located within schema
+#[derive(Clone, Debug)]
+pub struct Location<'s> {
+    /// Base URI of resource which contains subschema
+    pub base: Option<Uri>,
+    /// Resource which contains subschema
+    pub resource: &'s Value,
+    /// JSON Pointer to subschema within its resource
+    pub pointer: String,
+    /// Subschema itself
+    pub schema: &'s Value,
+}
+
+impl Location<'_> {
+    /// Absolute URI of subschema
+    pub fn uri(&self) -> Option<Uri> {
+        let mut uri = self.base.clone()?;
+        uri.set_fragment(Some(&self.pointer));
+        Some(uri)
+    }
+}
+
+/// Locating subschemas of bundled schema following references
+///
+/// Non-local references is resolved to resources embedded into schema by its ids.
+pub struct Locator<'s> {
+    root: &'s Value,
+    base: Option<Uri>,
+    /// Embedded resources by its ids (indexed on first use)
+    resources: OnceLock<HashMap<Uri, &'s Value>>,
+}
+
+impl<'s> Locator<'s> {
+    /// Create locator for schema
+    ///
+    /// The base URI is used when schema has no absolute id.
+    pub fn new(root: &'s Value, base: Option<&Uri>) -> Self {
+        let base = match resource_id(root) {
+            Some(id) => join(base, id),
+            None => base.cloned(),
+        };
+        Self {
+            root,
+            base: base.as_ref().map(without_fragment),
+            resources: OnceLock::new(),
+        }
+    }
+
+    /// Location of root schema
+    pub fn root(&self) -> Location<'s> {
+        Location {
+            base: self.base.clone(),
+            resource: self.root,
+            pointer: String::new(),
+            schema: self.root,
+        }
+    }
+
+    /// Locate subschema by schema path reported by validator
+    ///
+    /// References is followed at `$ref` segments and also when subschema has
+    /// no such child because not all validators includes `$ref` into paths.
+    pub fn locate(&self, schema_path: &str) -> Option<Location<'s>> {
+        let mut location = self.root();
+        for segment in schema_path.split('/').skip(1) {
+            let segment = segment.replace("~1", "/").replace("~0", "~");
+            location = if segment == "$ref" {
+                self.follow(&location)?
+            } else {
+                self.find(location, |location| self.child(location, &segment))?
+            };
+        }
+        Some(location)
+    }
+
+    /// Find something within subschema following its references until found
+    pub fn find<T>(
+        &self,
+        mut location: Location<'s>,
+        get: impl Fn(&Location<'s>) -> Option<T>,
+    ) -> Option<T> {
+        for _ in 0..REFS_LIMIT {
+            if let Some(found) = get(&location) {
+                return Some(found);
+            }
+            location = self.follow(&location)?;
+        }
+        None
+    }
+
+    /// Get child of subschema by property name or item index
+    pub fn child(&self, location: &Location<'s>, segment: &str) -> Option<Location<'s>> {
+        let schema = match location.schema {
+            Value::Object(object) => object.get(segment)?,
+            Value::Array(array) => array.get(segment.parse::<usize>().ok()?)?,
+            _ => return None,
+        };
+        Some(match resource_id(schema) {
+            Some(id) => Location {
+                base: join(location.base.as_ref(), id)
+                    .as_ref()
+                    .map(without_fragment),
+                resource: schema,
+                pointer: String::new(),
+                schema,
+            },
+            None => Location {
+                base: location.base.clone(),
+                resource: location.resource,
+                pointer: push_segment(&location.pointer, segment),
+                schema,
+            },
+        })
+    }
+
+    /// Follow reference of subschema if it has one
+    pub fn follow(&self, location: &Location<'s>) -> Option<Location<'s>> {
+        self.resolve(location, location.schema.get("$ref")?.as_str()?)
+    }
+
+    /// Resolve reference relative to subschema
+    ///
+    /// Only JSON Pointer fragments is supported.
+    pub fn resolve(&self, location: &Location<'s>, reference: &str) -> Option<Location<'s>> {
+        let (base, resource, fragment) = if let Some(fragment) = reference.strip_prefix('#') {
+            (
+                location.base.clone(),
+                location.resource,
+                fragment.to_string(),
+            )
+        } else {
+            let url = join(location.base.as_ref(), reference)?;
+            let id = without_fragment(&url);
+            let resource = if self.base.as_ref() == Some(&id) {
+                self.root
+            } else {
+                *self.resources().get(&id)?
+            };
+            let fragment = url.fragment().unwrap_or_default().to_string();
+            (Some(id), resource, fragment)
+        };
+        let pointer = percent_decode_str(&fragment)
+            .decode_utf8()
+            .ok()?
+            .into_owned();
+        let schema = resource.pointer(&pointer)?;
+        Some(Location {
+            base,
+            resource,
+            pointer,
+            schema,
+        })
+    }
+
+    fn resources(&self) -> &HashMap<Uri, &'s Value> {
+        self.resources.get_or_init(|| {
+            let mut resources = HashMap::new();
+            index_resources(self.root, self.base.as_ref(), &mut resources);
+            resources
+        })
+    }
+}
+
+/// Get identifier of schema which is a resource
+///
+/// Plain name fragments (like `#foo`) isn't resources.
+fn resource_id(schema: &Value) -> Option<&str> {
+    let object = schema.as_object()?;
+    ["$id", "id"]
+        .iter()
+        .find_map(|key| object.get(*key)?.as_str())
+        .filter(|id| !id.starts_with('#'))
+}
+
+/// Index embedded resources by its absolute ids
+fn index_resources<'s>(
+    schema: &'s Value,
+    base: Option<&Uri>,
+    resources: &mut HashMap<Uri, &'s Value>,
+) {
+    match schema {
+        Value::Object(object) => {
+            let id = resource_id(schema).and_then(|id| join(base, id));
+            if let Some(id) = &id {
+                resources.entry(without_fragment(id)).or_insert(schema);
+            }
+            let base = id.as_ref().or(base);
+            for (key, value) in object {
+                if !DATA_KEYWORDS.contains(&key.as_str()) {
+                    index_resources(value, base, resources);
+                }
+            }
+        }
+        Value::Array(array) => {
+            for value in array {
+                index_resources(value, base, resources);
+            }
+        }
+        _ => (),
+    }
+}
+
+/// Rewrite local references which points outside of definitions to embedded root
+fn rebase_refs(schema: &mut Value, defs_key: &str, root: bool) {
+    rewrite_local_refs(schema, root, &|reference| {
+        let pointer = &reference[1..];
+        if (pointer.is_empty() || pointer.starts_with('/'))
+            && !pointer.starts_with("/definitions/")
+            && !pointer.starts_with("/$defs/")
+        {
+            Some(format!("#/{}/{}{}", defs_key, ROOT_KEY, pointer))
+        } else {
+            None
+        }
+    });
+}
+
+/// Rewrite local references (which starts with `#`) of schema
+///
+/// Embedded resources with own ids is skipped because its references is relative to it.
+fn rewrite_local_refs(schema: &mut Value, root: bool, rewrite: &impl Fn(&str) -> Option<String>) {
+    match schema {
+        Value::Object(object) => {
+            let resource = ["$id", "id"].iter().any(|key| {
+                object
+                    .get(*key)
+                    .and_then(Value::as_str)
+                    .is_some_and(|id| !id.starts_with('#'))
+            });
+            if resource && !root {
+                return;
+            }
+            if let Some(Value::String(reference)) = object.get_mut("$ref") {
+                if reference.starts_with('#') {
+                    if let Some(rewritten) = rewrite(reference) {
+                        *reference = rewritten;
+                    }
+                }
+            }
+            for (key, value) in object.iter_mut() {
+                if !DATA_KEYWORDS.contains(&key.as_str()) {
+                    rewrite_local_refs(value, false, rewrite);
+                }
+            }
+        }
+        Value::Array(array) => {
+            for value in array {
+                rewrite_local_refs(value, false, rewrite);
+            }
+        }
+        _ => (),
+    }
+}
+
+/// Read and parse local document in any supported format
+fn fetch_file(url: &Uri) -> Result<Value> {
+    let topic = "referenced JSON Schema";
+    let path = url
+        .to_file_path()
+        .map_err(|_| Error::fetch(url.as_str(), "invalid file path"))?;
+    let mut file = utils::open_file(topic, &path)?;
+    let data = utils::read_input(topic, &path, &mut file)?;
+    // fallback to JSON when format cannot be determined from extension
+    Format::from_path(&path)
+        .unwrap_or(Format::Json)
+        .parse_data(topic, &path, &data)
+        .map_err(Error::into_schema)
+}
+
+fn schema_id(schema: &Value, id_key: &str) -> Option<Uri> {
+    schema.get(id_key)?.as_str()?.parse().ok()
+}
+
+fn without_fragment(url: &Uri) -> Uri {
+    let mut url = url.clone();
+    url.set_fragment(None);
+    url
+}
+
+/// Make definition key for resource
+///
+/// Slashes are avoided in keys because valico fails to resolve ids under such keys.
+fn resource_key(url: &Uri) -> String {
+    url.as_str()
+        .chars()
+        .map(|chr| match chr {
+            'a'..='z' | 'A'..='Z' | '0'..='9' | '.' | '-' | '_' => chr,
+            _ => '_',
+        })
+        .collect()
+}
+
+fn join(base: Option<&Uri>, reference: &str) -> Option<Uri> {
+    match base {
+        Some(base) => base.join(reference).ok(),
+        None => reference.parse().ok(),
+    }
+}
+
+/// Collect URLs of external documents which is referenced by schema
+///
+/// Identifiers of embedded resources also collected to prevent fetching it.
+/// Point references to other locations of resources which has own ids
+fn redirect_refs(
+    schema: &mut Value,
+    id_key: &str,
+    base: Option<&Uri>,
+    redirects: &HashMap<Uri, Uri>,
+) {
+    match schema {
+        Value::Object(object) => {
+            let id = object
+                .get(id_key)
+                .and_then(|id| id.as_str())
+                .and_then(|id| join(base, id));
+            let base = id.as_ref().or(base);
+
+            if let Some(Value::String(reference)) = object.get_mut("$ref") {
+                if let Some(url) = join(base, reference) {
+                    if let Some(id) = redirects.get(&without_fragment(&url)) {
+                        let mut id = id.clone();
+                        id.set_fragment(url.fragment());
+                        *reference = id.to_string();
+                    }
+                }
+            }
+
+            for (key, value) in object.iter_mut() {
+                if !DATA_KEYWORDS.contains(&key.as_str()) {
+                    redirect_refs(value, id_key, base, redirects);
+                }
+            }
+        }
+        Value::Array(array) => {
+            for value in array {
+                redirect_refs(value, id_key, base, redirects);
+            }
+        }
+        _ => (),
+    }
+}
+
+fn collect_refs(
+    schema: &Value,
+    id_key: &str,
+    base: Option<&Uri>,
+    urls: &mut Vec<Uri>,
+    ids: &mut HashSet<Uri>,
+) {
+    match schema {
+        Value::Object(object) => {
+            let id = object
+                .get(id_key)
+                .and_then(|id| id.as_str())
+                .and_then(|id| join(base, id));
+            if let Some(id) = &id {
+                ids.insert(without_fragment(id));
+            }
+            let base = id.as_ref().or(base);
+
+            if let Some(url) = object
+                .get("$ref")
+                .and_then(|reference| reference.as_str())
+                .and_then(|reference| join(base, reference))
+            {
+                urls.push(without_fragment(&url));
+            }
+
+            for (key, value) in object {
+                if !DATA_KEYWORDS.contains(&key.as_str()) {
+                    collect_refs(value, id_key, base, urls, ids);
+                }
+            }
+        }
+        Value::Array(array) => {
+            for value in array {
+                collect_refs(value, id_key, base, urls, ids);
+            }
+        }
+        _ => (),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Serve document on local HTTP server and count requests
+    #[cfg(all(feature = "http_req", feature = "jsonschema"))]
+    fn serve(
+        media_type: &'static str,
+        body: &'static str,
+    ) -> (String, std::sync::Arc<std::sync::atomic::AtomicUsize>) {
+        use std::io::{BufRead, BufReader, Write};
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let requests = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let counter = requests.clone();
+        std::thread::spawn(move || {
+            for mut stream in listener.incoming().flatten() {
+                let mut reader = BufReader::new(&stream);
+                let mut line = String::new();
+                while reader.read_line(&mut line).is_ok() && line.trim_end() != "" {
+                    line.clear();
+                }
+                counter.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+                let _ = write!(
+                    stream,
+                    "HTTP/1.1 200 OK\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    media_type,
+                    body.len(),
+                    body
+                );
+            }
+        });
+        (url, requests)
+    }
+
+    #[cfg(all(feature = "http_req", feature = "jsonschema"))]
+    #[test]
+    fn remote_refs() {
+        // media type takes precedence over extension
+        let (url, requests) = serve(
+            "application/schema+json",
+            r#"{ "definitions": { "port": { "type": "integer" } } }"#,
+        );
+        let dir = std::env::temp_dir().join(format!("jsonst-resolver-{}", std::process::id()));
+        let resolver = Resolver::new(Cache::open(Some(&dir), "schemas"));
+        let schema = json::json!({
+            "properties": {
+                "port": { "$ref": format!("{}/common.yaml#/definitions/port", url) }
+            }
+        });
+
+        for _ in 0..2 {
+            // jsonschema is built without resolving remote references by itself
+            let schema = resolver.bundle(schema.clone(), None);
+            let compiled = crate::Validator::JsonSchema
+                .compile_schema(&schema, None)
+                .unwrap();
+            let errors = compiled
+                .validate_data(&json::json!({ "port": "80" }))
+                .unwrap();
+            assert_eq!(errors.len(), 1);
+        }
+        // second bundling takes document from cache
+        #[cfg(feature = "cache")]
+        assert_eq!(requests.load(std::sync::atomic::Ordering::SeqCst), 1);
+        #[cfg(not(feature = "cache"))]
+        let _ = requests;
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn collect_external_refs() {
+        let schema = json::json!({
+            "$id": "https://example.com/schemas/root.json",
+            "properties": {
+                "a": { "$ref": "#/definitions/a" },
+                "b": { "$ref": "common.json#/definitions/b" },
+                "c": {
+                    "$id": "https://example.org/nested/",
+                    "items": { "$ref": "item.json" }
+                },
+                "d": {
+                    "const": { "$ref": "const.json" },
+                    "enum": [{ "$id": "https://example.org/enum.json" }],
+                    "examples": [{ "$ref": "example.json" }]
+                }
+            }
+        });
+        let mut urls = Vec::new();
+        let mut ids = HashSet::new();
+        collect_refs(&schema, "$id", None, &mut urls, &mut ids);
+        let urls = urls.iter().map(|url| url.as_str()).collect::<Vec<_>>();
+        assert!(urls.contains(&"https://example.com/schemas/root.json"));
+        assert!(urls.contains(&"https://example.com/schemas/common.json"));
+        assert!(urls.contains(&"https://example.org/nested/item.json"));
+        assert_eq!(urls.len(), 3);
+        assert_eq!(ids.len(), 2);
+    }
+
+    #[cfg(feature = "yaml")]
+    #[test]
+    fn bundle_local_files() {
+        let dir = std::env::temp_dir().join(format!("jsonst-bundle-{}", std::process::id()));
+        let common = dir.join("common").join("defs.yaml");
+        std::fs::create_dir_all(dir.join("schemas")).unwrap();
+        std::fs::create_dir_all(dir.join("common")).unwrap();
+        std::fs::write(&common, "definitions:\n  name:\n    type: string\n").unwrap();
+
+        let schema = json::json!({
+            "properties": { "name": { "$ref": "../common/defs.yaml#/definitions/name" } }
+        });
+        let base = Uri::from_file_path(dir.join("schemas").join("app.json")).unwrap();
+        let url = Uri::from_file_path(&common).unwrap();
+        let resolver = Resolver::new(Cache::open(None, "schemas"));
+        let schema = resolver.bundle(schema, Some(&base));
+
+        let resource = &schema["definitions"][resource_key(&url)];
+        assert_eq!(resource["$id"], url.as_str());
+        assert_eq!(resource["definitions"]["name"]["type"], "string");
+        assert_eq!(Resolver::bundled_files(&schema), vec![common.clone()]);
+
+        std::fs::remove_file(&common).unwrap();
+        assert!(fetch_file(&url).is_err());
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn bundle_resource_with_id() {
+        let dir = std::env::temp_dir().join(format!("jsonst-resource-id-{}", std::process::id()));
+        let common = dir.join("common.json");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(
+            &common,
+            r#"{"$id": "https://example.com/common.json", "definitions": {"port": {"type": "integer"}}}"#,
+        )
+        .unwrap();
+
+        let schema = json::json!({
+            "properties": { "port": { "$ref": "common.json#/definitions/port" } }
+        });
+        let base = Uri::from_file_path(dir.join("app.json")).unwrap();
+        let resolver = Resolver::new(Cache::open(None, "schemas"));
+        let schema = resolver.bundle(schema, Some(&base));
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let id = Uri::parse("https://example.com/common.json").unwrap();
+        let resource = &schema["definitions"][resource_key(&id)];
+        assert_eq!(resource["$id"], id.as_str());
+        assert_eq!(resource["definitions"]["port"]["type"], "integer");
+        assert_eq!(
+            schema["properties"]["port"]["$ref"],
+            "https://example.com/common.json#/definitions/port"
+        );
+
+        for validator in crate::Validator::ALL {
+            let errors = validator
+                .compile_schema(&schema, None)
+                .unwrap()
+                .validate_data(&json::json!({ "port": "80" }))
+                .unwrap();
+            assert_eq!(errors.len(), 1, "{}", validator);
+        }
+    }
+
+    #[test]
+    fn registry() {
+        let dir = std::env::temp_dir().join(format!("jsonst-registry-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("nested")).unwrap();
+        std::fs::write(
+            dir.join("nested").join("item.json"),
+            r#"{"$id": "https://example.com/item.json", "type": "integer"}"#,
+        )
+        .unwrap();
+        #[cfg(unix)]
+        std::os::unix::fs::symlink(&dir, dir.join("nested").join("loop")).unwrap();
+
+        let mut resolver = Resolver::new(Cache::open(None, "schemas"));
+        resolver.load_dir(&dir).unwrap();
+        assert!(resolver.load_dir(&dir.join("missing")).is_err());
+        assert_eq!(resolver.registry.len(), 1);
+
+        let schema = json::json!({ "items": { "$ref": "https://example.com/item.json" } });
+        let schema = resolver.bundle(schema, None);
+        let url = Uri::parse("https://example.com/item.json").unwrap();
+        assert_eq!(schema["definitions"][resource_key(&url)]["type"], "integer");
+        assert_eq!(
+            Locator::new(&schema, None)
+                .locate("/items/$ref/type")
+                .unwrap()
+                .schema,
+            "integer"
+        );
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn locate_subschemas() {
+        let schema = json::json!({
+            "$id": "https://example.com/root.json",
+            "properties": {
+                "a": { "$ref": "common.json#/definitions/a%25" },
+                "b": { "$ref": "#/properties/a" }
+            },
+            "definitions": {
+                "common": {
+                    "$id": "common.json",
+                    "definitions": { "a%": { "type": "string" } }
+                }
+            }
+        });
+        let locator = Locator::new(&schema, None);
+        let uri = |path| locator.locate(path).and_then(|location| location.uri());
+
+        assert_eq!(
+            uri("/properties/a/$ref/type").unwrap().as_str(),
+            "https://example.com/common.json#/definitions/a%/type"
+        );
+        // validators may omit `$ref` in paths
+        let location = locator.locate("/properties/b/type").unwrap();
+        assert_eq!(location.pointer, "/definitions/a%/type");
+        assert_eq!(location.schema, "string");
+        assert_eq!(
+            uri("/definitions/common").unwrap().as_str(),
+            "https://example.com/common.json#"
+        );
+        assert!(locator.locate("/properties/c").is_none());
+    }
+
+    #[test]
+    fn narrow_subschema() {
+        let root = json::json!({
+            "$id": "https://example.com/root.json",
+            "properties": {
+                "list": { "type": "array", "items": { "$ref": "#/definitions/node" } }
+            },
+            "definitions": {
+                "node": {
+                    "properties": {
+                        "item": { "$ref": "#/definitions/item" },
+                        "children": { "$ref": "#/properties/list" },
+                        "root": { "$ref": "#" }
+                    }
+                },
+                "item": { "enum": [{ "$ref": "#" }] },
+                "other": { "$id": "https://example.com/other.json", "items": { "$ref": "#" } }
+            }
+        });
+        let schema = Resolver::narrow(&root, None, "/definitions/node").unwrap();
+        let properties = &schema["properties"];
+        assert_eq!(schema["$id"], "https://example.com/root.json");
+        assert_eq!(properties["item"]["$ref"], "#/definitions/item");
+        assert_eq!(
+            properties["children"]["$ref"],
+            "#/definitions/jsonst-root/properties/list"
+        );
+        assert_eq!(properties["root"]["$ref"], "#/definitions/jsonst-root");
+        assert_eq!(
+            schema.pointer("/definitions/node/properties/children/$ref"),
+            Some(&json::json!("#/definitions/jsonst-root/properties/list"))
+        );
+        assert_eq!(
+            schema.pointer("/definitions/jsonst-root/properties/list/items/$ref"),
+            Some(&json::json!("#/definitions/node"))
+        );
+        assert!(schema.pointer("/definitions/jsonst-root/$id").is_none());
+        assert_eq!(schema["definitions"]["item"]["enum"][0]["$ref"], "#");
+        assert_eq!(schema["definitions"]["other"]["items"]["$ref"], "#");
+
+        // references is followed and local ones of embedded resource is made absolute
+        let schema =
+            Resolver::narrow(&root, None, "/properties/list/items/$ref/properties/item").unwrap();
+        assert_eq!(schema["$ref"], "#/definitions/item");
+        let schema = Resolver::narrow(&root, None, "/definitions/other/items").unwrap();
+        assert_eq!(schema["$ref"], "https://example.com/other.json#");
+        assert!(Resolver::narrow(&root, None, "/definitions/missing").is_none());
+    }
+
+    #[test]
+    fn foreign_refs() {
+        let rebased = |mut schema: Value| {
+            rebase_refs(&mut schema, "definitions", true);
+            schema
+        };
+        let schema =
+            json::json!({ "oneOf": [{ "$ref": "#/definitions/a" }], "definitions": { "a": {} } });
+        assert_eq!(rebased(schema.clone()), schema);
+        assert_eq!(
+            rebased(json::json!({ "items": { "$ref": "#" } })),
+            json::json!({ "items": { "$ref": "#/definitions/jsonst-root" } })
+        );
+        let schema = json::json!({ "definitions": { "b": { "$id": "http://b", "items": { "$ref": "#" } } } });
+        assert_eq!(rebased(schema.clone()), schema);
+        let schema = json::json!({ "const": { "$ref": "#" } });
+        assert_eq!(rebased(schema.clone()), schema);
+    }
+}
@@ -0,0 +1,220 @@
+/*!
+
+Suggesting closest valid names for unknown properties and enum values
+
+*/
+
+use super::{Location, Locator, ValidationError};
+use json::{Map, Value};
+
+/// Add suggestions to errors of `additionalProperties`, `unevaluatedProperties` and `enum`
+///
+/// Failed keyword is found by its schema path.
+pub fn add_suggestions(schema: &Value, data: &Value, errors: &mut [ValidationError]) {
+    let locator = Locator::new(schema, None);
+    for error in errors {
+        let (node, instance) = match (
+            keyword_parent(&locator, &error.schema_path, &error.keyword),
+            data.pointer(&error.instance_path),
+        ) {
+            (Some(node), Some(instance)) => (node.schema, instance),
+            _ => continue,
+        };
+        let suggestions = match error.keyword.as_str() {
+            "additionalProperties" | "unevaluatedProperties" => {
+                property_suggestions(node, instance, &error.message)
+            }
+            "enum" => enum_suggestions(node, instance),
+            _ => continue,
+        };
+        if !suggestions.is_empty() {
+            error.message = format!(
+                "{} (did you mean {}?)",
+                error.message,
+                suggestions.join(", ")
+            );
+        }
+    }
+}
+
+/// Suggest known property names instead of unknown ones which is mentioned in message
+fn property_suggestions(node: &Value, instance: &Value, message: &str) -> Vec<String> {
+    let object = match instance.as_object() {
+        Some(object) => object,
+        None => return Vec::new(),
+    };
+    let empty = Map::new();
+    let properties = node
+        .get("properties")
+        .and_then(Value::as_object)
+        .unwrap_or(&empty);
+    let names = properties
+        .keys()
+        .map(String::as_str)
+        .chain(
+            node.get("patternProperties")
+                .and_then(Value::as_object)
+                .into_iter()
+                .flat_map(|patterns| patterns.keys())
+                .filter_map(|pattern| literal_pattern(pattern)),
+        )
+        .collect::<Vec<_>>();
+
+    object
+        .keys()
+        .filter(|key| !properties.contains_key(*key) && mentions(message, key))
+        .filter_map(|key| {
+            let name = closest(key, &names)?;
+            Some(format!("'{}' instead of '{}'", name, key))
+        })
+        .collect()
+}
+
+/// Suggest closest string from enum
+fn enum_suggestions(node: &Value, instance: &Value) -> Vec<String> {
+    let (value, variants) = match (
+        instance.as_str(),
+        node.get("enum").and_then(Value::as_array),
+    ) {
+        (Some(value), Some(variants)) => (value, variants),
+        _ => return Vec::new(),
+    };
+    let names = variants
+        .iter()
+        .filter_map(Value::as_str)
+        .collect::<Vec<_>>();
+    closest(value, &names)
+        .map(|name| format!("'{}'", name))
+        .into_iter()
+        .collect()
+}
+
+/// Check that name is quoted in message
+fn mentions(message: &str, name: &str) -> bool {
+    message.contains(&format!("'{}'", name)) || message.contains(&Value::from(name).to_string())
+}
+
+/// Get name which is matched by pattern like `^name$` exactly
+fn literal_pattern(pattern: &str) -> Option<&str> {
+    let name = pattern.strip_prefix('^')?.strip_suffix('$')?;
+    if name.is_empty() || name.contains(|chr| "\\.*+?()[]{}|^$".contains(chr)) {
+        None
+    } else {
+        Some(name)
+    }
+}
+
+/// Find closest name within reasonable edit distance
+fn closest<'n>(name: &str, names: &[&'n str]) -> Option<&'n str> {
+    let limit = (name.chars().count() / 3).max(1);
+    names
+        .iter()
+        .map(|candidate| (distance(name, candidate), *candidate))
+        .filter(|(distance, _)| *distance <= limit)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate)
+}
+
+/// Edit distance where transposition of adjacent characters is single edit
+fn distance(a: &str, b: &str) -> usize {
+    let a = a.chars().collect::<Vec<_>>();
+    let b = b.chars().collect::<Vec<_>>();
+    // rows of distances for two previous prefixes of `a`
+    let mut prev2 = vec![0; b.len() + 1];
+    let mut prev = (0..=b.len()).collect::<Vec<_>>();
+    let mut row = vec![0; b.len() + 1];
+
+    for i in 1..=a.len() {
+        row[0] = i;
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            row[j] = (prev[j] + 1).min(row[j - 1] + 1).min(prev[j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                row[j] = row[j].min(prev2[j - 2] + 1);
+            }
+        }
+        std::mem::swap(&mut prev2, &mut prev);
+        std::mem::swap(&mut prev, &mut row);
+    }
+    prev[b.len()]
+}
+
+/// Find subschema which contains failed keyword
+///
+/// References are followed even if it isn't in path because not all validators includes it.
+pub(super) fn keyword_parent<'s>(
+    locator: &Locator<'s>,
+    schema_path: &str,
+    keyword: &str,
+) -> Option<Location<'s>> {
+    let pointer = schema_path.strip_suffix(keyword)?.strip_suffix('/')?;
+    let location = locator.locate(pointer)?;
+    locator.find(location, |location| {
+        location.schema.get(keyword).map(|_| location.clone())
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Validator;
+
+    #[test]
+    fn edit_distance() {
+        assert_eq!(distance("replcias", "replicas"), 1);
+        assert_eq!(distance("always", "Always"), 1);
+        assert_eq!(distance("nmae", "name"), 1);
+        assert_eq!(distance("kitten", "sitting"), 3);
+        assert_eq!(distance("", "abc"), 3);
+        assert_eq!(
+            closest("replcias", &["policy", "replicas"]),
+            Some("replicas")
+        );
+        assert_eq!(closest("foo", &["policy", "replicas"]), None);
+    }
+
+    #[test]
+    fn suggest_names() {
+        let schema = json::json!({
+            "type": "object",
+            "properties": {
+                "replicas": { "type": "integer" },
+                "policy": { "enum": ["Always", "Never"] },
+                "spec": { "$ref": "#/definitions/spec" }
+            },
+            "patternProperties": { "^labels$": { "type": "object" } },
+            "additionalProperties": false,
+            "definitions": {
+                "spec": {
+                    "properties": { "name": { "type": "string" } },
+                    "additionalProperties": false
+                }
+            }
+        });
+        let data = json::json!({
+            "replcias": 1,
+            "lables": {},
+            "extra": true,
+            "policy": "always",
+            "spec": { "nmae": "app" }
+        });
+        for validator in Validator::ALL {
+            let compiled = validator.compile_schema(&schema, None).unwrap();
+            let errors = compiled.validate_data(&data).unwrap();
+            let messages = errors
+                .iter()
+                .map(|error| error.message.as_str())
+                .collect::<Vec<_>>()
+                .join("\n");
+            for expected in [
+                "'replicas' instead of 'replcias'",
+                "'labels' instead of 'lables'",
+                "did you mean 'Always'?",
+                "did you mean 'name' instead of 'nmae'?",
+            ] {
+                assert!(messages.contains(expected), "{}: {}", validator, messages);
+            }
+            assert!(!messages.contains("instead of 'extra'"));
+        }
+    }
+}
@@ -4,54 +4,207 @@
 
 */
 
-use super::{Error, Path, Result, Standard};
-use valico::json_schema::Scope;
+use super::{Error, Result, Standard, ValidationError};
+use std::sync::{Mutex, MutexGuard, PoisonError};
+use valico::{
+    common::error::ValicoError,
+    json_schema::{schema::ScopedSchema, Scope},
+};
+
+/// Supported standards
+///
+/// Valico implements mostly draft 6 with some draft 7 keywords.
+pub const STANDARDS: &[Standard] = &[Standard::Draft4, Standard::Draft6, Standard::Draft7];
 
 pub struct CompiledSchema<'c> {
-    scope: Scope,
+    /// Compiled scopes which isn't in use now
+    ///
+    /// Valico scope cannot be shared between threads so each thread takes own.
+    scopes: Mutex<Vec<Scope>>,
     url: url::Url,
-    _phantom: core::marker::PhantomData<&'c char>,
+    schema: &'c json::Value,
 }
 
 impl<'c> CompiledSchema<'c> {
     pub fn compile(schema: &'c json::Value, _std: Option<Standard>) -> Result<Self> {
+        let (scope, url) = Self::compile_scope(schema)?;
+        Ok(Self {
+            scopes: Mutex::new(vec![scope]),
+            url,
+            schema,
+        })
+    }
+
+    fn compile_scope(schema: &json::Value) -> Result<(Scope, url::Url)> {
         let mut scope = Scope::new();
         scope
             .compile(schema.clone(), false)
             .map_err(|error| {
-                log::error!("Unable to compile JSON Schema due to: {}", error);
-                Error::Compile
+                Error::Compile(format!("Unable to compile JSON Schema due to: {}", error))
             })
-            .map(|url| Self {
-                scope,
-                url,
-                _phantom: core::marker::PhantomData,
+            .map(|url| (scope, url))
+    }
+
+    pub fn source(&self) -> &'c json::Value {
+        self.schema
+    }
+
+    pub fn validate(&self, data: &json::Value) -> Result<Vec<ValidationError>> {
+        self.with_schema(|schema| {
+            schema
+                .validate(data)
+                .errors
+                .iter()
+                .map(|error| self.conv_error(error.as_ref()))
+                .collect()
+        })
+    }
+
+    pub fn is_valid(&self, data: &json::Value) -> Result<bool> {
+        self.with_schema(|schema| schema.validate(data).is_valid())
+    }
+
+    fn lock_scopes(&self) -> MutexGuard<'_, Vec<Scope>> {
+        self.scopes.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Use compiled schema from scope which isn't in use now
+    fn with_schema<T>(&self, action: impl FnOnce(ScopedSchema) -> T) -> Result<T> {
+        let scope = self.lock_scopes().pop();
+        let scope = if let Some(scope) = scope {
+            scope
+        } else {
+            Self::compile_scope(self.schema)?.0
+        };
+        let result = scope
+            .resolve(&self.url)
+            .ok_or_else(|| {
+                Error::Compile(format!(
+                    "Unable to resolve previously compiled valico JSON Schema: {}",
+                    self.url
+                ))
             })
+            .map(action);
+        self.lock_scopes().push(scope);
+        result
     }
 
-    pub fn validate(&self, path: &Path, data: &json::Value, quiet: bool) -> Result<u32> {
-        let schema = self.scope.resolve(&self.url).ok_or_else(|| {
-            log::error!(
-                "Unable to resolve previously compiled valico JSON Schema: {}",
-                self.url
-            );
-            Error::Query
-        })?;
-        let result = schema.validate(data);
+    fn conv_error(&self, error: &dyn ValicoError) -> ValidationError {
+        let keyword = conv_code(error.get_code(), error.get_detail());
+        let mut instance_path = error.get_path();
+        let mut message = if let Some(detail) = error.get_detail() {
+            format!("{}: {}", error.get_title(), detail)
+        } else {
+            error.get_title().into()
+        };
+        if keyword == "required" {
+            // valico points to missing property instead of object itself
+            let split = instance_path.rfind('/').unwrap_or(0);
+            message = format!("{}: {}", message, &instance_path[split + 1..]);
+            instance_path = &instance_path[..split];
+        }
+        ValidationError {
+            schema_path: keyword_location(self.schema, instance_path, &keyword),
+            instance_path: instance_path.into(),
+            keyword,
+            message,
+            detail: Some(format!("valico error code: {}", error.get_code())),
+        }
+    }
+}
 
-        Ok(if result.errors.is_empty() {
-            if !quiet {
-                println!("Data is valid");
+/// Convert valico error code to schema keyword
+fn conv_code(code: &str, detail: Option<&str>) -> String {
+    match code {
+        "wrong_type" => "type".into(),
+        "properties" if detail.map(|detail| detail.starts_with("Additional")) == Some(true) => {
+            "additionalProperties".into()
+        }
+        _ => {
+            let mut parts = code.split('_');
+            let mut keyword = String::from(parts.next().unwrap_or_default());
+            for part in parts {
+                let mut chars = part.chars();
+                if let Some(first) = chars.next() {
+                    keyword.extend(first.to_uppercase());
+                    keyword.push_str(chars.as_str());
+                }
             }
-            0
-        } else if !quiet {
-            println!("Data is not valid");
-            for error in &result.errors {
-                eprintln!("{} {}", path.display(), error)
+            keyword
+        }
+    }
+}
+
+/// Guess keyword location by walking schema along instance location
+///
+/// Because valico doesn't track schema paths we follows `properties`,
+/// `additionalProperties`, `items` and local `$ref`s to find it.
+fn keyword_location(root: &json::Value, instance_location: &str, keyword: &str) -> String {
+    let mut location = String::new();
+    let mut schema = root;
+
+    for segment in instance_location.split('/').skip(1) {
+        schema = follow_refs(root, schema, &mut location);
+        let (path, next) = if let Some(next) = schema
+            .get("properties")
+            .and_then(|properties| properties.get(segment))
+        {
+            (format!("/properties/{}", segment), next)
+        } else if let Some(next) = schema.get("items").and_then(|items| {
+            if items.is_array() {
+                items.get(segment.parse::<usize>().ok()?)
+            } else {
+                Some(items)
             }
-            result.errors.len() as u32
+        }) {
+            if schema["items"].is_array() {
+                (format!("/items/{}", segment), next)
+            } else {
+                ("/items".into(), next)
+            }
+        } else if let Some(next) = schema
+            .get("additionalProperties")
+            .or_else(|| schema.get("additionalItems"))
+            .filter(|next| next.is_object())
+        {
+            let keyword = if schema.get("additionalProperties").is_some() {
+                "/additionalProperties"
+            } else {
+                "/additionalItems"
+            };
+            (keyword.into(), next)
         } else {
-            1
-        })
+            break;
+        };
+        location.push_str(&path);
+        schema = next;
+    }
+    follow_refs(root, schema, &mut location);
+
+    location.push('/');
+    location.push_str(keyword);
+    location
+}
+
+/// Follow local `$ref`s
+fn follow_refs<'s>(
+    root: &'s json::Value,
+    mut schema: &'s json::Value,
+    location: &mut String,
+) -> &'s json::Value {
+    // limit depth to prevent infinite recursion
+    for _ in 0..16 {
+        if let Some(target) = schema
+            .get("$ref")
+            .and_then(|reference| reference.as_str())
+            .and_then(|reference| reference.strip_prefix('#'))
+            .and_then(|pointer| root.pointer(pointer))
+        {
+            location.push_str("/$ref");
+            schema = target;
+        } else {
+            break;
+        }
     }
+    schema
 }
@@ -0,0 +1,175 @@
+/*!
+
+Watching files for changes
+
+*/
+
+use crate::{Error, Result};
+use notify::{
+    event::{AccessKind, AccessMode},
+    Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher as _,
+};
+use std::{
+    collections::{BTreeSet, HashMap},
+    path::{Path, PathBuf},
+    sync::mpsc::{channel, Receiver, RecvTimeoutError},
+    time::Duration,
+};
+
+/// Delay to collect events which comes together
+///
+/// Editors usually makes several operations when saving files.
+const SETTLE_DELAY: Duration = Duration::from_millis(100);
+
+/// Files watcher
+///
+/// Directories of files is watched instead of files itself because editors
+/// often replaces files by renaming. Given directories is watched recursively.
+pub struct Watcher {
+    watcher: RecommendedWatcher,
+    events: Receiver<notify::Result<Event>>,
+    /// Watched directories with recursive flag
+    dirs: HashMap<PathBuf, bool>,
+}
+
+impl Watcher {
+    /// Create new watcher
+    pub fn new() -> Result<Self> {
+        let (sender, events) = channel();
+        let watcher = notify::recommended_watcher(sender)
+            .map_err(|source| Error::Watch { path: None, source })?;
+        Ok(Self {
+            watcher,
+            events,
+            dirs: HashMap::new(),
+        })
+    }
+
+    /// Watch files or directories
+    ///
+    /// Already watched directories is skipped.
+    pub fn watch<'p>(&mut self, paths: impl IntoIterator<Item = &'p Path>) -> Result<()> {
+        for path in paths {
+            let (dir, recursive) = if path.is_dir() {
+                (path.canonicalize().unwrap_or_else(|_| path.into()), true)
+            } else if let Some(dir) = absolute(path).parent() {
+                (dir.into(), false)
+            } else {
+                continue;
+            };
+            if self.is_watched(&dir, recursive) {
+                continue;
+            }
+            if self.dirs.remove(&dir).is_some() {
+                // watched non-recursively before
+                let _ = self.watcher.unwatch(&dir);
+            }
+            let mode = if recursive {
+                RecursiveMode::Recursive
+            } else {
+                RecursiveMode::NonRecursive
+            };
+            self.watcher
+                .watch(&dir, mode)
+                .map_err(|source| Error::Watch {
+                    path: Some(dir.clone()),
+                    source,
+                })?;
+            log::debug!("Watching directory '{}'", dir.display());
+            self.dirs.insert(dir, recursive);
+        }
+        Ok(())
+    }
+
+    fn is_watched(&self, dir: &Path, recursive: bool) -> bool {
+        self.dirs
+            .get(dir)
+            .is_some_and(|watched| *watched || !recursive)
+            || dir
+                .ancestors()
+                .skip(1)
+                .any(|parent| self.dirs.get(parent) == Some(&true))
+    }
+
+    /// Wait for changes
+    ///
+    /// Returns absolute paths of changed files.
+    pub fn wait(&self) -> Result<BTreeSet<PathBuf>> {
+        let mut changed = BTreeSet::new();
+        let event = self.events.recv().map_err(|_| disconnected())?;
+        add_changes(event, &mut changed)?;
+        // collect events which comes soon
+        loop {
+            match self.events.recv_timeout(SETTLE_DELAY) {
+                Ok(event) => add_changes(event, &mut changed)?,
+                Err(RecvTimeoutError::Timeout) => break,
+                Err(RecvTimeoutError::Disconnected) => return Err(disconnected()),
+            }
+        }
+        Ok(changed)
+    }
+}
+
+/// Add paths of event which means that file contents may be changed
+fn add_changes(event: notify::Result<Event>, changed: &mut BTreeSet<PathBuf>) -> Result<()> {
+    let event = event.map_err(|source| Error::Watch { path: None, source })?;
+    match event.kind {
+        EventKind::Create(_)
+        | EventKind::Modify(_)
+        | EventKind::Remove(_)
+        | EventKind::Access(AccessKind::Close(AccessMode::Write)) => {
+            changed.extend(event.paths);
+        }
+        _ => (),
+    }
+    Ok(())
+}
+
+fn disconnected() -> Error {
+    Error::Watch {
+        path: None,
+        source: notify::Error::generic("watcher is stopped"),
+    }
+}
+
+/// Make absolute path comparable with paths of changed files
+///
+/// Only directory is canonicalized because file may not exist.
+pub fn absolute(path: &Path) -> PathBuf {
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    match (dir.canonicalize(), path.file_name()) {
+        (Ok(dir), Some(name)) => dir.join(name),
+        _ => path.into(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn watch_changes() {
+        let dir = std::env::temp_dir().join(format!("jsonst-watch-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("nested")).unwrap();
+        let file = dir.join("data.json");
+        std::fs::write(&file, "{}").unwrap();
+
+        let mut watcher = Watcher::new().unwrap();
+        watcher.watch([file.as_path()]).unwrap();
+        std::fs::write(&file, "[]").unwrap();
+        let changed = watcher.wait().unwrap();
+        assert_eq!(changed.into_iter().collect::<Vec<_>>(), [absolute(&file)]);
+
+        // directories is watched recursively
+        let nested = dir.join("nested").join("data.json");
+        watcher.watch([dir.as_path()]).unwrap();
+        std::fs::write(&nested, "{}").unwrap();
+        let changed = watcher.wait().unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(changed.into_iter().collect::<Vec<_>>(), [absolute(&nested)]);
+    }
+}